@@ -1,24 +1,310 @@
 /* auto-generated by NAPI-RS */
 /* eslint-disable */
+export declare function addExitRule(rule: ExitRule4Node): number
+
+export declare function bundleBuy(param: BundleBuyParam4Node): Promise<Array<BundleBuyOutcome>>
+
+export interface BundleBuy4Node {
+  secretKey?: string
+  amountIn: string
+  walletId?: string
+}
+
+export interface BundleBuyOutcome {
+  wallet: string
+  bundleIndex: number
+  tokenAmount: string
+  maxSolCost: string
+  signature?: string
+  response?: string
+  error?: string
+}
+
+export interface BundleBuyParam4Node {
+  swap: SwapParam4Node
+  wallets: Array<BundleBuy4Node>
+}
+
+export declare function closeEmptyTokenAccounts(connection: string, secretKey?: string | undefined | null, walletId?: string | undefined | null): Promise<CloseTokenAccountsResult>
+
+export interface CloseTokenAccountsResult {
+  closed: Array<string>
+  signatures: Array<string>
+}
+
+export declare function configureRelayClient(config: RelayClientConfig4Node): void
+
+export interface CopyTradeEvent {
+  copyId: number
+  targetSignature: string
+  mint: string
+  side: string
+  targetSolAmount: string
+  targetTokenAmount: string
+  amountIn: string
+  responses: Array<string>
+  error?: string
+}
+
+export declare function createSeedAccount(param: SeedAccountParam4Node): Promise<SeedAccountResult>
+
+export interface EndpointOutcome {
+  relay: string
+  url: string
+  signature: string
+  response?: string
+  error?: string
+  elapsedMs: number
+}
+
+export interface ExitRule4Node {
+  id?: number
+  wallet: string
+  mint: string
+  entrySol: string
+  tokenAmount: string
+  takeProfitBps?: number
+  stopLossBps?: number
+  trailingStopBps?: number
+  marketCapSol?: string
+  peakValue?: string
+  status?: string
+}
+
+export interface ExitTriggerEvent {
+  ruleId: number
+  mint: string
+  reason: string
+  value: string
+  entrySol: string
+  responses: Array<string>
+  error?: string
+}
+
+export declare function getMetrics(): string
+
+export declare function getPnl(connection: string, wallet?: string | undefined | null, mint?: string | undefined | null): Promise<Array<WalletPnl4Node>>
+
+export declare function getRealizedFill(connection: string, signature: string, wallet?: string | undefined | null): Promise<RealizedFill4Node>
+
+export declare function getRelayHealth(): Array<RelayHealth4Node>
+
+export declare function getSeedAccount(publicKey: string, seed: string): string
+
+export declare function importWallet(label: string, secretKey: string): string
+
+export interface KeepAliveTarget4Node {
+  relay: string
+  url?: string
+  authKey?: string
+  intervalMs?: number
+}
+
 export declare function keepaliveBloxroute(apiKey: string, apiUrl: string): Promise<void>
 
+export declare function keepaliveNextblock(apiUrl: string, authKey: string): Promise<void>
+
 export declare function keepaliveNodeme(apiUrl: string): Promise<void>
 
 export declare function keepaliveSlot(apiUrl: string, authKey: string): Promise<void>
 
 export declare function keepaliveTemporal(apiUrl: string): Promise<void>
 
+export interface LatencyBreakdown {
+  parseMs: number
+  buildMs: number
+  rpcMs: number
+  blockhashMs: number
+  signMs: number
+  sendMs: number
+  relayHttp: Array<RelayLatency>
+  totalMs: number
+}
+
+export declare function launch(param: LaunchParam4Node): Promise<LaunchResult>
+
+export interface LaunchParam4Node {
+  swap: SwapParam4Node
+  name: string
+  symbol: string
+  uri: string
+  mintSecretKey?: string
+  bundleBuys?: Array<BundleBuy4Node>
+}
+
+export interface LaunchResult {
+  mint: string
+  responses: Array<string>
+}
+
+export declare function listExitRules(): Array<ExitRule4Node>
+
+export declare function listWallets(): Array<WalletInfo>
+
+export declare function loadWallet(label: string, path: string, password?: string | undefined | null): string
+
+export interface Position4Node {
+  wallet: string
+  mint: string
+  tokenAmount: string
+  costBasis: string
+  realizedPnl: string
+  markValue?: string
+  unrealizedPnl?: string
+  fees: string
+  tips: string
+  rent: string
+  buys: number
+  sells: number
+}
+
+export declare function prewarmRelays(relays?: Array<string> | undefined | null, idleMs?: number | undefined | null, regionFanOut?: number | undefined | null): Promise<Array<PrewarmResult>>
+
+export interface PrewarmResult {
+  relay: string
+  url: string
+  coldMs?: number
+  warmMs?: number
+  setupMs?: number
+  error?: string
+}
+
+export declare function probeRegions(relays?: Array<string> | undefined | null): Promise<Array<RegionProbe4Node>>
+
+export interface PumpfunEvent4Node {
+  kind: string
+  signature: string
+  slot: number
+  mint: string
+  user: string
+  timestamp: number
+  bondingCurve?: string
+  creator?: string
+  name?: string
+  symbol?: string
+  uri?: string
+  isBuy?: boolean
+  solAmount?: string
+  tokenAmount?: string
+  virtualSolReserves?: string
+  virtualTokenReserves?: string
+}
+
+export interface RealizedFill4Node {
+  signature: string
+  slot: number
+  blockTime?: number
+  success: boolean
+  error?: string
+  wallet: string
+  mint?: string
+  isBuy?: boolean
+  tokenDelta: string
+  solDelta: string
+  swapSolDelta: string
+  fee: string
+  tip: string
+  relay?: string
+  rent: string
+  computeUnits?: string
+}
+
+export declare function recordFill(connection: string, signature: string, wallet?: string | undefined | null): Promise<RealizedFill4Node>
+
+export interface RegionProbe4Node {
+  relay: string
+  region: string
+  host: string
+  rttMs?: number
+  error?: string
+}
+
+export interface RelayClientConfig4Node {
+  relay: string
+  timeoutMs?: number
+  connectTimeoutMs?: number
+  poolIdleTimeoutMs?: number
+  poolMaxIdlePerHost?: number
+  tcpKeepaliveMs?: number
+  http2PriorKnowledge?: boolean
+  localAddress?: string
+  interface?: string
+  scheme?: string
+  authInHeader?: boolean
+  caCertPaths?: Array<string>
+  pinnedCerts?: Array<string>
+}
+
+export interface RelayHealth4Node {
+  relay: string
+  url: string
+  healthy: boolean
+  lastRttMs?: number
+  lastStatus?: number
+  lastError?: string
+  consecutiveFailures: number
+  pings: number
+  failures: number
+  lastCheckedMs: number
+}
+
+export interface RelayLatency {
+  relay: string
+  url: string
+  httpMs: number
+}
+
+export declare function removeExitRule(ruleId: number): boolean
+
+export declare function resetMetrics(): void
+
+export declare function saveEncryptedWallet(label: string, path: string, password: string): void
+
+export interface SeedAccountParam4Node {
+  connection: string
+  secretKey?: string
+  seed: string
+  mint: string
+  lamports?: string
+  walletId?: string
+}
+
+export interface SeedAccountResult {
+  publicKey: string
+  signature: string
+}
+
+export declare function setLogLevel(level: string): void
+
 export interface SolAccountStruct {
   publicKey: string
   seed: string
 }
 
+export declare function startCopyTrade(param: SwapParam4Node, wsUrl: string, ratioBps: number | undefined | null, callback: ((err: Error | null, arg: CopyTradeEvent) => any)): Promise<number>
+
+export declare function startExitWatcher(param: SwapParam4Node, wsUrl: string, callback: ((err: Error | null, arg: ExitTriggerEvent) => any)): Promise<number>
+
+export declare function startGeyserListener(endpoint: string, xToken: string | undefined | null, callback: ((err: Error | null, arg: PumpfunEvent4Node) => any)): Promise<number>
+
+export declare function startKeepAlive(targets: Array<KeepAliveTarget4Node>): Promise<void>
+
+export declare function startListener(wsUrl: string, callback: ((err: Error | null, arg: PumpfunEvent4Node) => any)): Promise<number>
+
+export declare function stopCopyTrade(copyId: number): boolean
+
+export declare function stopKeepAlive(): number
+
+export declare function stopListener(listenerId: number): boolean
+
 export declare function swap(param: SwapParam4Node): Promise<Array<string>>
 
+export declare function swapDetailed(param: SwapParam4Node): Promise<TradeReport>
+
 export interface SwapParam4Node {
   connection: string
   connectionBrand: string
-  secretKey: string
+  secretKey?: string
   amountIn: string
   amountOut: string
   fixedSide: string
@@ -82,4 +368,31 @@ export interface SwapParam4Node {
   tradeManualLocalRpc?: string
   antiMev?: string
   creatorVault?: string
+  pumpfunDirect?: boolean
+  sellMode?: string
+  regionFanOut?: string
+  walletId?: string
+}
+
+export declare function sweepSeedAccount(param: SeedAccountParam4Node): Promise<SeedAccountResult>
+
+export interface TradeReport {
+  signatures: Array<string>
+  outcomes: Array<EndpointOutcome>
+  latency: LatencyBreakdown
+}
+
+export declare function unloadWallet(label: string): boolean
+
+export interface WalletInfo {
+  label: string
+  publicKey: string
+}
+
+export interface WalletPnl4Node {
+  wallet: string
+  realizedPnl: string
+  unrealizedPnl: string
+  costBasis: string
+  positions: Array<Position4Node>
 }
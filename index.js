@@ -393,8 +393,42 @@ if (!nativeBinding) {
 }
 
 module.exports = nativeBinding
+module.exports.addExitRule = nativeBinding.addExitRule
+module.exports.bundleBuy = nativeBinding.bundleBuy
+module.exports.closeEmptyTokenAccounts = nativeBinding.closeEmptyTokenAccounts
+module.exports.configureRelayClient = nativeBinding.configureRelayClient
+module.exports.createSeedAccount = nativeBinding.createSeedAccount
+module.exports.getMetrics = nativeBinding.getMetrics
+module.exports.getPnl = nativeBinding.getPnl
+module.exports.getRealizedFill = nativeBinding.getRealizedFill
+module.exports.getRelayHealth = nativeBinding.getRelayHealth
+module.exports.getSeedAccount = nativeBinding.getSeedAccount
+module.exports.importWallet = nativeBinding.importWallet
 module.exports.keepaliveBloxroute = nativeBinding.keepaliveBloxroute
+module.exports.keepaliveNextblock = nativeBinding.keepaliveNextblock
 module.exports.keepaliveNodeme = nativeBinding.keepaliveNodeme
 module.exports.keepaliveSlot = nativeBinding.keepaliveSlot
 module.exports.keepaliveTemporal = nativeBinding.keepaliveTemporal
+module.exports.launch = nativeBinding.launch
+module.exports.listExitRules = nativeBinding.listExitRules
+module.exports.listWallets = nativeBinding.listWallets
+module.exports.loadWallet = nativeBinding.loadWallet
+module.exports.prewarmRelays = nativeBinding.prewarmRelays
+module.exports.probeRegions = nativeBinding.probeRegions
+module.exports.recordFill = nativeBinding.recordFill
+module.exports.removeExitRule = nativeBinding.removeExitRule
+module.exports.resetMetrics = nativeBinding.resetMetrics
+module.exports.saveEncryptedWallet = nativeBinding.saveEncryptedWallet
+module.exports.setLogLevel = nativeBinding.setLogLevel
+module.exports.startCopyTrade = nativeBinding.startCopyTrade
+module.exports.startExitWatcher = nativeBinding.startExitWatcher
+module.exports.startGeyserListener = nativeBinding.startGeyserListener
+module.exports.startKeepAlive = nativeBinding.startKeepAlive
+module.exports.startListener = nativeBinding.startListener
+module.exports.stopCopyTrade = nativeBinding.stopCopyTrade
+module.exports.stopKeepAlive = nativeBinding.stopKeepAlive
+module.exports.stopListener = nativeBinding.stopListener
 module.exports.swap = nativeBinding.swap
+module.exports.swapDetailed = nativeBinding.swapDetailed
+module.exports.sweepSeedAccount = nativeBinding.sweepSeedAccount
+module.exports.unloadWallet = nativeBinding.unloadWallet
@@ -0,0 +1,25 @@
+use solana_program::pubkey::Pubkey;
+use std::collections::HashSet;
+use std::sync::{OnceLock, RwLock};
+
+// token accounts we have read from chain, lets buys skip the create instruction without an
+// rpc round trip. a relay accepting a buy says nothing about it landing, so sent buys are
+// not recorded and keep the idempotent create until the account is seen
+
+static KNOWN_ATAS: OnceLock<RwLock<HashSet<Pubkey>>> = OnceLock::new();
+
+fn known_atas() -> &'static RwLock<HashSet<Pubkey>> {
+    KNOWN_ATAS.get_or_init(|| RwLock::new(HashSet::new()))
+}
+
+pub fn is_known_ata(account: &Pubkey) -> bool {
+    known_atas().read().unwrap().contains(account)
+}
+
+pub fn mark_known_ata(account: &Pubkey) {
+    known_atas().write().unwrap().insert(*account);
+}
+
+pub fn forget_ata(account: &Pubkey) {
+    known_atas().write().unwrap().remove(account);
+}
@@ -0,0 +1,127 @@
+use anyhow::{anyhow, Result};
+use borsh::from_slice;
+use solana_client::rpc_client::RpcClient;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::configs::global::PUMPFUN_PROGRAM_ID;
+use crate::contracts::pumpfun_proxy::BondingCurveInfo;
+use crate::utils::sighash;
+
+// bonding curve states pushed by a streaming source (geyser account updates).
+// entries are only trusted while that source is connected, it clears the cache when it
+// disconnects or is stopped. completed curves are dropped, idle ones evicted by the source.
+// completion is final, so completed curves are remembered separately and survive a clear
+
+const BONDING_CURVE_LEN: usize = 81;
+
+struct CachedCurve {
+    slot: u64,
+    updated: Instant,
+    info: BondingCurveInfo,
+}
+
+static BONDING_CURVES: OnceLock<RwLock<HashMap<Pubkey, CachedCurve>>> = OnceLock::new();
+
+fn bonding_curves() -> &'static RwLock<HashMap<Pubkey, CachedCurve>> {
+    BONDING_CURVES.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+static COMPLETED_CURVES: OnceLock<RwLock<HashSet<Pubkey>>> = OnceLock::new();
+
+fn completed_curves() -> &'static RwLock<HashSet<Pubkey>> {
+    COMPLETED_CURVES.get_or_init(|| RwLock::new(HashSet::new()))
+}
+
+// true once the curve was seen complete, from the stream or from rpc
+pub fn is_bonding_curve_complete(bonding_curve: &Pubkey) -> bool {
+    completed_curves().read().unwrap().contains(bonding_curve)
+}
+
+pub fn decode_bonding_curve(data: &[u8]) -> Option<BondingCurveInfo> {
+    if data.len() < BONDING_CURVE_LEN || data[..8] != sighash("account", "BondingCurve") {
+        return None;
+    }
+    from_slice::<BondingCurveInfo>(&data[..BONDING_CURVE_LEN]).ok()
+}
+
+// keep the newest state per curve, updates can arrive out of order. a completed curve
+// never trades again, it is dropped and later lookups go to rpc
+pub fn update_bonding_curve(
+    bonding_curve: Pubkey,
+    slot: u64,
+    info: BondingCurveInfo,
+) {
+    let mut curves = bonding_curves().write().unwrap();
+    if matches!(curves.get(&bonding_curve), Some(cached) if cached.slot > slot) {
+        return;
+    }
+    if info.complete {
+        curves.remove(&bonding_curve);
+        completed_curves().write().unwrap().insert(bonding_curve);
+    } else {
+        curves.insert(bonding_curve, CachedCurve { slot, updated: Instant::now(), info });
+    }
+}
+
+pub fn get_cached_bonding_curve(bonding_curve: &Pubkey) -> Option<BondingCurveInfo> {
+    bonding_curves().read().unwrap()
+        .get(bonding_curve)
+        .map(|cached| cached.info.clone())
+}
+
+// drop curves that have not changed for `max_idle`, returns how many were evicted
+pub fn evict_idle_bonding_curves(max_idle: Duration) -> usize {
+    let mut curves = bonding_curves().write().unwrap();
+    let before = curves.len();
+    curves.retain(|_, cached| cached.updated.elapsed() < max_idle);
+    before - curves.len()
+}
+
+pub fn clear_bonding_curves() {
+    bonding_curves().write().unwrap().clear();
+}
+
+pub fn get_bonding_curve_pda(mint: &Pubkey) -> Result<Pubkey> {
+    let (bonding_curve, _bump) = Pubkey::find_program_address(
+        &[
+            b"bonding-curve",
+            mint.as_ref(),
+        ],
+        &Pubkey::from_str(PUMPFUN_PROGRAM_ID)?,
+    );
+    Ok(bonding_curve)
+}
+
+// curve state for `mint`, from the cache when a stream keeps it current, otherwise from rpc
+pub fn get_bonding_curve(
+    rpc_client: &RpcClient,
+    mint: &Pubkey,
+) -> Result<Option<BondingCurveInfo>> {
+    let bonding_curve = get_bonding_curve_pda(mint)?;
+
+    if let Some(info) = get_cached_bonding_curve(&bonding_curve) {
+        return Ok(Some(info));
+    }
+
+    let account = rpc_client
+        .get_account_with_commitment(&bonding_curve, CommitmentConfig::processed())
+        .map_err(|e| anyhow!("Failed to fetch bonding curve account: {}", e))?
+        .value;
+
+    match account {
+        Some(account) if account.data.len() >= BONDING_CURVE_LEN => {
+            let info = from_slice::<BondingCurveInfo>(&account.data[..BONDING_CURVE_LEN])
+                .map_err(|e| anyhow!("Failed to deserialize bonding curve account: {}", e))?;
+            if info.complete {
+                completed_curves().write().unwrap().insert(bonding_curve);
+            }
+            Ok(Some(info))
+        },
+        _ => Ok(None),
+    }
+}
@@ -147,6 +147,46 @@ pub fn get_random_jito_tip_account(bribe: &u64) -> Pubkey {
 }
 
 
+// which relay a tip account belongs to, used to tell which leg of a fan-out landed
+pub fn get_tip_account_sender(tip_account: &str) -> Option<u8> {
+    let tables: [(u8, &[&str]); 6] = [
+        (SENDER_JITO, JITO_TIP_ACCOUNTS),
+        (SENDER_BLOXROUTE, BLOXROUTE_TIP_ACCOUNTS),
+        (SENDER_TEMPORAL, TEMPORAL_TIP_ACCOUNTS),
+        (SENDER_NEXT_BLOCK, NEXTBLOCK_TIP_ACCOUNTS),
+        (SENDER_SLOT0_TRADE, SLOT0_TRADE_TIP_ACCOUNTS),
+        (SENDER_NODE1_ME, NODE1ME_TIP_ACCOUNTS),
+    ];
+    tables.iter()
+        .find(|(_, accounts)| accounts.contains(&tip_account))
+        .map(|(sender, _)| *sender)
+}
+
+pub fn get_sender_name(sender: u8) -> &'static str {
+    match sender {
+        SENDER_JITO => "jito",
+        SENDER_BLOXROUTE => "bloxroute",
+        SENDER_TEMPORAL => "temporal",
+        SENDER_NEXT_BLOCK => "nextblock",
+        SENDER_SLOT0_TRADE => "0slot",
+        SENDER_NODE1_ME => "node1me",
+        _ => "unknown",
+    }
+}
+
+pub fn parse_sender_name(name: &str) -> Option<u8> {
+    match name.to_lowercase().as_str() {
+        "jito" => Some(SENDER_JITO),
+        "bloxroute" => Some(SENDER_BLOXROUTE),
+        "temporal" => Some(SENDER_TEMPORAL),
+        "nextblock" => Some(SENDER_NEXT_BLOCK),
+        "0slot" | "slot0" => Some(SENDER_SLOT0_TRADE),
+        "node1me" | "node1" => Some(SENDER_NODE1_ME),
+        _ => None,
+    }
+}
+
+
 pub fn send_normal_or_not(send_normal_trade: bool) -> bool{
     send_normal_trade
 }
@@ -159,8 +199,8 @@ pub fn send_jito_or_not(
     other_block_bundle_bribe: Option<u64>,
     block_engine_url: &Option<String>,
 ) -> bool {
-    if let Some(bl) = block_engine_url {
-        if let Some(bribe) = jito_bribe { true }
+    if block_engine_url.is_some() {
+        if jito_bribe.is_some() { true }
         else {
             if block_number.is_some() &&
                 first_block_bundle_bribe.is_some() &&
@@ -177,7 +217,7 @@ pub fn send_slot0_trade_or_not(
     block_engine_url: &Option<String>,
 ) -> bool {
     const MIN_BRIBE: u64 = 100_000; // 0.0001
-    if let Some(block_engine) = block_engine_url {
+    if block_engine_url.is_some() {
         if let Some(bribe) = slot0_trade_bribe {
             if bribe >= MIN_BRIBE { true } else { false }
         }
@@ -191,7 +231,7 @@ pub fn send_bloxroute_or_not(
     block_engine_url: &Option<String>,
 ) -> bool {
     const MIN_BRIBE: u64 = 2_000_000; // 0.002
-    if let Some(block_engine) = block_engine_url {
+    if block_engine_url.is_some() {
         if let Some(bribe) = bloxroute_bribe {
             if bribe >= MIN_BRIBE { true } else { false }
         }
@@ -205,7 +245,7 @@ pub fn send_temporal_or_not(
     block_engine_url: &Option<String>,
 ) -> bool {
     const MIN_BRIBE: u64 = 1_000_000; // 0.001
-    if let Some(block_engine) = block_engine_url {
+    if block_engine_url.is_some() {
         if let Some(bribe) = temporal_bribe {
             if bribe >= MIN_BRIBE { true } else { false }
         }
@@ -219,7 +259,7 @@ pub fn send_nextblock_or_not(
     block_engine_url: &Option<String>,
 ) -> bool {
     const MIN_BRIBE: u64 = 1_000_000; // 0.001
-    if let Some(block_engine) = block_engine_url {
+    if block_engine_url.is_some() {
         if let Some(bribe) = nextblock_bribe {
             if bribe >= MIN_BRIBE { true } else { false }
         }
@@ -234,7 +274,7 @@ pub fn send_node1me_or_not(
 ) -> bool {
 
     const MIN_BRIBE: u64 = 2_000_000; // 0.002
-    if let Some(block_engine) = block_engine_url {
+    if block_engine_url.is_some() {
         if let Some(bribe) = node1me_bribe {
             if bribe >= MIN_BRIBE { true } else { false }
         }
@@ -1,11 +1,40 @@
 use solana_program::pubkey::Pubkey;
 
 pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+// marker the js side passes as token_in/token_out for sol, not the wsol mint. on-chain
+// derivations use spl_token::native_mint::ID
 pub const NATIVE_MINT: &str = "So11111111111111111111111111111111111111111";
 pub const JITO_MEV_PREVENT_ADDR: &str = "jitodontfront111111111111111111111111111111";
 
 pub const PUMPFUN_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
 pub const PUMPFUN_FEE_RECIPIENT: &str = "CebN5WGQ4jvEPvsVU4EoHEpgzq1VV7AbicfhtW4xC9iM";
+pub const PUMPFUN_FEE_BPS: u64 = 100;
+
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+pub const MPL_TOKEN_METADATA_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+
+pub const PUMPSWAP_PROGRAM_ID: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
+pub const PUMPSWAP_PROTOCOL_FEE_RECIPIENTS: &[&str] = &[
+    "62qc2CNXwrYqQScmEdiZFFAnJR262PxWEuNQtxfafNgV",
+    "7VtfL8fvgNfhz17qKRMjzQEXgbdpnHHHQRh54R9jP2RJ",
+    "7hTckgnGnLQR6sdH7YkqFTAA7VwTfYFaZ6EhEsU3saCX",
+    "9rPYyANsfQZw3DnDmKE3YCQF5E8oD89UXoHn9JFEhJUz",
+    "AVmoTthdrX6tKt4nDjco2D775W2YK3sDhxPcMmzUAmTY",
+    "FWsW1xNtWscwNmKv6wVsU1iTzRN6wmmk3MjxRP5tT7hz",
+    "G5UZAVbAf46s7cKWoyKu8kYTip9DGTpbLZ2qa9Aq69dP",
+    "JCRGumoE9Qi5BBgULTgdgTLjSgkCMSbF62ZZfGs84JeU",
+];
+pub const PUMPSWAP_LP_FEE_BPS: u64 = 20;
+pub const PUMPSWAP_PROTOCOL_FEE_BPS: u64 = 5;
+pub const PUMPSWAP_COIN_CREATOR_FEE_BPS: u64 = 5;
+
+pub const RAYDIUM_AMM_V4_PROGRAM_ID: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+pub const RAYDIUM_AMM_AUTHORITY: &str = "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1";
+
+//SwapParam.target_pool
+pub const TARGET_POOL_PUMPFUN: &str = "pumpfun";
+pub const TARGET_POOL_PUMPSWAP: &str = "pumpswap";
+pub const TARGET_POOL_RAYDIUM: &str = "raydium";
 
 pub const VALIDATE_COMPILE_PROGRAM_ID: &str = "";
 pub const GROUP_VALIDATOR_ACCOUNT: &str = "";
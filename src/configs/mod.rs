@@ -1,2 +1,3 @@
 pub mod global;
-pub mod bribe;
\ No newline at end of file
+pub mod bribe;
+pub mod regions;
@@ -0,0 +1,93 @@
+use crate::configs::bribe::*;
+
+// block engine / relay endpoints per region, (region, host)
+
+const JITO_REGIONS: &[(&str, &str)] = &[
+    ("ams", "amsterdam.mainnet.block-engine.jito.wtf"),
+    ("fra", "frankfurt.mainnet.block-engine.jito.wtf"),
+    ("lon", "london.mainnet.block-engine.jito.wtf"),
+    ("ny", "ny.mainnet.block-engine.jito.wtf"),
+    ("slc", "slc.mainnet.block-engine.jito.wtf"),
+    ("tokyo", "tokyo.mainnet.block-engine.jito.wtf"),
+];
+
+const TEMPORAL_REGIONS: &[(&str, &str)] = &[
+    ("ny", "ewr1.nozomi.temporal.xyz"),
+    ("pit", "pit1.nozomi.temporal.xyz"),
+    ("fra", "fra2.nozomi.temporal.xyz"),
+    ("ams", "ams1.nozomi.temporal.xyz"),
+    ("tokyo", "tyo1.nozomi.temporal.xyz"),
+    ("sgp", "sgp1.nozomi.temporal.xyz"),
+    ("la", "lax1.nozomi.temporal.xyz"),
+];
+
+const SLOT0_TRADE_REGIONS: &[(&str, &str)] = &[
+    ("ny", "ny.0slot.trade"),
+    ("fra", "de.0slot.trade"),
+    ("ams", "ams.0slot.trade"),
+    ("tokyo", "jp.0slot.trade"),
+    ("la", "la.0slot.trade"),
+];
+
+const BLOXROUTE_REGIONS: &[(&str, &str)] = &[
+    ("ny", "ny.solana.dex.blxrbdn.com"),
+    ("lon", "uk.solana.dex.blxrbdn.com"),
+    ("fra", "germany.solana.dex.blxrbdn.com"),
+    ("ams", "amsterdam.solana.dex.blxrbdn.com"),
+    ("tokyo", "tokyo.solana.dex.blxrbdn.com"),
+    ("la", "la.solana.dex.blxrbdn.com"),
+];
+
+const NEXTBLOCK_REGIONS: &[(&str, &str)] = &[
+    ("ny", "ny.nextblock.io"),
+    ("fra", "fra.nextblock.io"),
+    ("lon", "london.nextblock.io"),
+    ("tokyo", "tokyo.nextblock.io"),
+    ("slc", "slc.nextblock.io"),
+];
+
+const NODE1ME_REGIONS: &[(&str, &str)] = &[
+    ("ny", "ny.node1.me"),
+    ("fra", "fra.node1.me"),
+    ("ams", "ams.node1.me"),
+    ("tokyo", "tk.node1.me"),
+];
+
+pub fn get_relay_regions(relay: u8) -> &'static [(&'static str, &'static str)] {
+    match relay {
+        SENDER_JITO => JITO_REGIONS,
+        SENDER_BLOXROUTE => BLOXROUTE_REGIONS,
+        SENDER_TEMPORAL => TEMPORAL_REGIONS,
+        SENDER_NEXT_BLOCK => NEXTBLOCK_REGIONS,
+        SENDER_SLOT0_TRADE => SLOT0_TRADE_REGIONS,
+        SENDER_NODE1_ME => NODE1ME_REGIONS,
+        _ => &[],
+    }
+}
+
+pub fn get_region_host(relay: u8, region: &str) -> Option<&'static str> {
+    get_relay_regions(relay).iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(region))
+        .map(|(_, host)| *host)
+}
+
+// `block_engine_locate` is either regions for every relay ("fra" or "fra,ams") or per relay
+// lists ("jito=fra,ams;temporal=ny"). returns the regions asked for `relay`
+pub fn parse_block_engine_locate(locate: &str, relay: u8) -> Vec<String> {
+    let split_regions = |regions: &str| -> Vec<String> {
+        regions.split(',')
+            .map(|region| region.trim().to_string())
+            .filter(|region| !region.is_empty())
+            .collect()
+    };
+
+    let locate = locate.trim();
+    if !locate.contains('=') {
+        return split_regions(locate);
+    }
+    locate.split(';')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(name, _)| parse_sender_name(name.trim()) == Some(relay))
+        .map(|(_, regions)| split_regions(regions))
+        .unwrap_or_default()
+}
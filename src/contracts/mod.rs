@@ -2,4 +2,10 @@ pub mod group_validate_compile;
 pub mod pumpfun_proxy;
 pub mod jito_trick;
 
-pub mod remain_balance_check;
\ No newline at end of file
+pub mod remain_balance_check;
+pub mod wsol;
+pub mod pumpswap;
+pub mod raydium_amm;
+pub mod pumpfun;
+pub mod pumpfun_idl;
+pub mod pumpfun_global;
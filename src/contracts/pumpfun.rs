@@ -0,0 +1,140 @@
+use anyhow::Result;
+use solana_program::instruction::Instruction;
+use solana_program::pubkey::Pubkey;
+use crate::contracts::pumpfun_idl::{
+    build_account_metas,
+    PumpfunAccountInputs,
+    BUY_ACCOUNTS,
+    CREATE_ACCOUNTS,
+    SELL_ACCOUNTS,
+};
+use crate::utils::sighash;
+
+const BONDING_CURVE_SEED: &str = "bonding-curve";
+
+// native pump.fun instructions, no proxy program in between
+pub struct Pumpfun {
+    pumpfun_program_id: Pubkey,
+}
+
+impl Pumpfun {
+
+    pub fn new(
+        pumpfun_program_id: Pubkey,
+    ) -> Self {
+        Pumpfun {
+            pumpfun_program_id,
+        }
+    }
+
+    pub fn get_bonding_curve_pda(
+        &self,
+        mint: &Pubkey,
+    ) -> Pubkey {
+        let (bonding_curve, _bump) = Pubkey::find_program_address(
+            &[
+                BONDING_CURVE_SEED.as_bytes(),
+                mint.as_ref(),
+            ],
+            &self.pumpfun_program_id,
+        );
+        bonding_curve
+    }
+
+    pub fn get_account_inputs(
+        &self,
+        mint: &Pubkey,
+        coin_ata: &Pubkey,
+        payer: &Pubkey,
+        fee_recipient: &Pubkey,
+        creator: &Pubkey,
+        creator_vault: Option<&Pubkey>,
+    ) -> Result<PumpfunAccountInputs> {
+        let mut overrides = vec![];
+        if let Some(creator_vault) = creator_vault {
+            overrides.push(("creator_vault", *creator_vault));
+        }
+
+        Ok(PumpfunAccountInputs {
+            mint: *mint,
+            user: *payer,
+            associated_user: *coin_ata,
+            fee_recipient: *fee_recipient,
+            creator: *creator,
+            overrides,
+        })
+    }
+
+    // `inputs` from get_account_inputs
+    pub fn get_buy_instruction(
+        &self,
+        amount: &u64,
+        max_sol_cost: &u64,
+        inputs: &PumpfunAccountInputs,
+    ) -> Result<Instruction> {
+        let accounts = build_account_metas(BUY_ACCOUNTS, inputs, &self.pumpfun_program_id)?;
+        let mut data = sighash("global", "buy").to_vec();
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&max_sol_cost.to_le_bytes());
+
+        Ok(Instruction {
+            program_id: self.pumpfun_program_id,
+            accounts,
+            data,
+        })
+    }
+
+    // `inputs` from get_account_inputs
+    pub fn get_sell_instruction(
+        &self,
+        amount: &u64,
+        min_sol_output: &u64,
+        inputs: &PumpfunAccountInputs,
+    ) -> Result<Instruction> {
+        let accounts = build_account_metas(SELL_ACCOUNTS, inputs, &self.pumpfun_program_id)?;
+        let mut data = sighash("global", "sell").to_vec();
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&min_sol_output.to_le_bytes());
+
+        Ok(Instruction {
+            program_id: self.pumpfun_program_id,
+            accounts,
+            data,
+        })
+    }
+
+    // create a new mint with its bonding curve, `mint` has to sign the transaction
+    pub fn get_create_instruction(
+        &self,
+        name: &str,
+        symbol: &str,
+        uri: &str,
+        mint: &Pubkey,
+        payer: &Pubkey,
+        creator: &Pubkey,
+    ) -> Result<Instruction> {
+        let inputs = PumpfunAccountInputs {
+            mint: *mint,
+            user: *payer,
+            associated_user: Pubkey::default(),
+            fee_recipient: Pubkey::default(),
+            creator: *creator,
+            overrides: vec![],
+        };
+        let accounts = build_account_metas(CREATE_ACCOUNTS, &inputs, &self.pumpfun_program_id)?;
+
+        let mut data = sighash("global", "create").to_vec();
+        for value in [name, symbol, uri] {
+            data.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            data.extend_from_slice(value.as_bytes());
+        }
+        data.extend_from_slice(creator.as_ref());
+
+        Ok(Instruction {
+            program_id: self.pumpfun_program_id,
+            accounts,
+            data,
+        })
+    }
+
+}
@@ -0,0 +1,226 @@
+use anyhow::{anyhow, Result};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_client::rpc_client::RpcClient;
+use solana_program::pubkey::Pubkey;
+use crate::configs::global::*;
+use crate::contracts::pumpfun_idl::get_global_pda;
+use crate::contracts::pumpfun_proxy::BondingCurveInfo;
+use crate::utils::sighash;
+
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+const GLOBAL_CACHE_TTL: Duration = Duration::from_secs(60);
+
+// pump.fun `Global` account, only the leading fields are decoded
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+pub struct PumpfunGlobal {
+    pub discriminator: u64,
+    pub initialized: bool,
+    pub authority: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub initial_virtual_token_reserves: u64,
+    pub initial_virtual_sol_reserves: u64,
+    pub initial_real_token_reserves: u64,
+    pub token_total_supply: u64,
+    pub fee_basis_points: u64,
+    pub withdraw_authority: Pubkey,
+    pub enable_migrate: bool,
+    pub pool_migration_fee: u64,
+    pub creator_fee_basis_points: u64,
+    pub fee_recipients: [Pubkey; 7],
+    pub set_creator_authority: Pubkey,
+}
+
+impl PumpfunGlobal {
+
+    pub fn total_fee_bps(&self) -> u64 {
+        self.fee_basis_points + self.creator_fee_basis_points
+    }
+
+    // state of a bonding curve right after `create`, used to quote the dev buy
+    pub fn initial_bonding_curve(&self, creator: &Pubkey) -> BondingCurveInfo {
+        BondingCurveInfo {
+            discriminator: 0,
+            virtual_token_reserves: self.initial_virtual_token_reserves,
+            virtual_sol_reserves: self.initial_virtual_sol_reserves,
+            real_token_reserves: self.initial_real_token_reserves,
+            real_sol_reserves: 0,
+            token_total_supply: self.token_total_supply,
+            complete: false,
+            creator: *creator,
+        }
+    }
+
+    pub fn all_fee_recipients(&self) -> Vec<Pubkey> {
+        let mut recipients = vec![self.fee_recipient];
+        recipients.extend(
+            self.fee_recipients.iter()
+                .filter(|r| **r != Pubkey::default())
+                .cloned()
+        );
+        recipients
+    }
+
+    // round robin over every recipient so concurrent trades lock different accounts
+    pub fn next_fee_recipient(&self) -> Pubkey {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let recipients = self.all_fee_recipients();
+        let index = NEXT.fetch_add(1, Ordering::Relaxed) % recipients.len();
+        recipients[index]
+    }
+}
+
+static GLOBAL_CACHE: OnceLock<RwLock<Option<(Instant, PumpfunGlobal)>>> = OnceLock::new();
+
+fn global_cache() -> &'static RwLock<Option<(Instant, PumpfunGlobal)>> {
+    GLOBAL_CACHE.get_or_init(|| RwLock::new(None))
+}
+
+// the account carries more fields after the decoded ones, they are left unread
+pub fn decode_pumpfun_global(data: &[u8]) -> Result<PumpfunGlobal> {
+    if data.len() < 8 || data[..8] != sighash("account", "Global") {
+        return Err(anyhow!("not a pump.fun global account"));
+    }
+    PumpfunGlobal::deserialize(&mut &data[..])
+        .map_err(|e| anyhow!("Failed to deserialize pump.fun global account: {}", e))
+}
+
+pub fn fetch_pumpfun_global(
+    connection: &RpcClient,
+) -> Result<PumpfunGlobal> {
+    let global = get_global_pda(&Pubkey::from_str(PUMPFUN_PROGRAM_ID)?);
+    let account_data = connection.get_account_data(&global)
+        .map_err(|e| anyhow!("pump.fun global account not found: {}", e))?;
+
+    decode_pumpfun_global(&account_data)
+}
+
+pub fn refresh_pumpfun_global(
+    connection: &RpcClient,
+) -> Result<PumpfunGlobal> {
+    let global = fetch_pumpfun_global(connection)?;
+    *global_cache().write().unwrap() = Some((Instant::now(), global.clone()));
+    Ok(global)
+}
+
+// cached Global account, refreshed once it is older than GLOBAL_CACHE_TTL.
+// a failed refresh keeps serving the stale copy
+pub fn get_pumpfun_global(
+    connection: &RpcClient,
+) -> Result<PumpfunGlobal> {
+    let cached = global_cache().read().unwrap().clone();
+
+    match cached {
+        Some((fetched_at, global)) if fetched_at.elapsed() < GLOBAL_CACHE_TTL => Ok(global),
+        Some((_, global)) => {
+            match refresh_pumpfun_global(connection) {
+                Ok(fresh) => Ok(fresh),
+                Err(e) => {
+                    warn!("pump.fun global refresh failed, using stale copy: {}", e);
+                    Ok(global)
+                }
+            }
+        },
+        None => refresh_pumpfun_global(connection),
+    }
+}
+
+// fee recipient and total fee bps for the next trade, falls back to the static config
+pub fn get_fee_params(
+    connection: &RpcClient,
+) -> (Pubkey, u64) {
+    match get_pumpfun_global(connection) {
+        Ok(global) => (global.next_fee_recipient(), global.total_fee_bps()),
+        Err(e) => {
+            warn!("pump.fun global unavailable: {}", e);
+            (Pubkey::from_str(PUMPFUN_FEE_RECIPIENT).unwrap(), PUMPFUN_FEE_BPS)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{Engine as _, engine::general_purpose};
+    use std::collections::HashSet;
+
+    // leading fields of a Global account in the base64 form getAccountInfo returns. fee
+    // recipients and curve parameters are the mainnet ones, the authorities are placeholders
+    const GLOBAL_ACCOUNT: &str = concat!(
+        "p+joschscn8BOjvoxlU44tTFNlMxwZNusVmdpy75fmxnUQjId+JE2oatEeak/ClEpPqCUb74FUJuG/soxrZkZndg",
+        "fGrZ9WamRgAQ2EfjzwMAAKwj/AYAAAAAeMX7UdECAACAxqR+jQMAXwAAAAAAAAA3Ptf0poNItBU2KoRXX6bJeTFb",
+        "tP+gmgrjXI8gyoclVgHB4eQAAAAAAAUAAAAAAAAASsL40N1cvJfjKJwZfLUGKlTz2Va5zm5RFfllZ6pcs+ZgjMwd",
+        "/OlhtDt3nBkVBabi079F1aTbRhitdsgtYXVFNWODcwAOo8GFMYIs8TsDmrubXQe8bRSTBok6Vfi+21tug4R0KS5n",
+        "WpS0NuywqZiJQjKKg93GIzgClhJnxc1hF8uNGBoMhJ+pN6bzSt7TCB75VwCqywybs9kJpLkUdSek69eqj7Bg2Ckb",
+        "TE1HXa/3Yslr3A2s6zbAEurRLtOpSEFh4ATIfOuY+lzkf4A4Bv0seUXSlSSVmuwA3tl4FPOPeEYoKSVH2hQWgVZK",
+        "l3T4A43aWky6aQzHHb+MqbBCocA3NA==",
+    );
+
+    const FEE_RECIPIENTS: [&str; 7] = [
+        "62qc2CNXwrYqQScmEdiZFFAnJR262PxWEuNQtxfafNgV",
+        "7VtfL8fvgNfhz17qKRMjzQEXgbdpnHHHQRh54R9jP2RJ",
+        "7hTckgnGzjNtbSCpMRo5jXpJJuBFNcfhuUK8JUEWK4Xw",
+        "9rPYyANsfQZw3DnDmKE3YCQF5E8oD89UXoHn9JFEhJUz",
+        "AVmoTthdrX6tKt4nDjco2D775W2YK3sDhxPcMmzUAmTY",
+        "FWsW1xNtWscwNmKv6wVsU1iTzRN6wmmk3MjxRP5tT7hz",
+        "G5UZAVbAf46s7cKWoyKu8kYTip9DGTpbLZ2qa9Aq69dP",
+    ];
+
+    fn global() -> PumpfunGlobal {
+        let mut data = general_purpose::STANDARD.decode(GLOBAL_ACCOUNT).unwrap();
+        data.extend_from_slice(&[0u8; 64]);
+        decode_pumpfun_global(&data).unwrap()
+    }
+
+    #[test]
+    fn decodes_the_global_account() {
+        let global = global();
+        assert!(global.initialized);
+        assert_eq!(global.fee_recipient, Pubkey::from_str(PUMPFUN_FEE_RECIPIENT).unwrap());
+        assert_eq!(global.initial_virtual_token_reserves, 1_073_000_000_000_000);
+        assert_eq!(global.initial_virtual_sol_reserves, 30_000_000_000);
+        assert_eq!(global.initial_real_token_reserves, 793_100_000_000_000);
+        assert_eq!(global.token_total_supply, 1_000_000_000_000_000);
+        assert_eq!((global.fee_basis_points, global.creator_fee_basis_points), (95, 5));
+        assert_eq!(global.total_fee_bps(), 100);
+        assert!(global.enable_migrate);
+        assert_eq!(global.pool_migration_fee, 15_000_001);
+        assert_eq!(
+            global.fee_recipients.map(|r| r.to_string()),
+            FEE_RECIPIENTS.map(|r| r.to_string()),
+        );
+
+        let curve = global.initial_bonding_curve(&Pubkey::default());
+        assert_eq!(curve.get_buy_token_amount(1_000_000_000, global.total_fee_bps()), 34_281_150_129_545);
+    }
+
+    #[test]
+    fn rejects_other_accounts() {
+        let mut data = general_purpose::STANDARD.decode(GLOBAL_ACCOUNT).unwrap();
+        data[0] ^= 1;
+        assert!(decode_pumpfun_global(&data).is_err());
+        assert!(decode_pumpfun_global(&[]).is_err());
+    }
+
+    #[test]
+    fn fee_recipients_rotate_through_every_account() {
+        let mut global = global();
+        global.fee_recipients[6] = Pubkey::default();
+        let recipients = global.all_fee_recipients();
+        assert_eq!(recipients.len(), 7);
+        assert_eq!(recipients[0], global.fee_recipient);
+        assert!(!recipients.contains(&Pubkey::default()));
+
+        // consecutive picks walk the list in order, wrapping around
+        let picks: Vec<Pubkey> = (0..recipients.len() * 2).map(|_| global.next_fee_recipient()).collect();
+        let start = recipients.iter().position(|r| *r == picks[0]).unwrap();
+        for (i, pick) in picks.iter().enumerate() {
+            assert_eq!(*pick, recipients[(start + i) % recipients.len()]);
+        }
+        assert_eq!(picks.iter().collect::<HashSet<_>>().len(), recipients.len());
+    }
+}
@@ -0,0 +1,681 @@
+use anyhow::{anyhow, Result};
+use solana_program::instruction::AccountMeta;
+use solana_program::pubkey::Pubkey;
+use crate::configs::global::*;
+
+use std::str::FromStr;
+
+// account lists of the pump.fun IDL (6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P),
+// only what is needed to resolve addresses and lock flags
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IdlInput {
+    Mint,
+    User,
+    AssociatedUser,
+    FeeRecipient,
+    Creator,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum IdlSeed {
+    Const(&'static [u8]),
+    Input(IdlInput),
+    // address of another account of the same instruction
+    Account(&'static str),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum IdlAccountSource {
+    Address(&'static str),
+    Input(IdlInput),
+    // program None means the pump.fun program itself
+    Pda {
+        seeds: &'static [IdlSeed],
+        program: Option<&'static str>,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct IdlAccount {
+    pub name: &'static str,
+    pub writable: bool,
+    pub signer: bool,
+    pub source: IdlAccountSource,
+}
+
+const GLOBAL: IdlAccount = IdlAccount {
+    name: "global",
+    writable: false,
+    signer: false,
+    source: IdlAccountSource::Pda { seeds: &[IdlSeed::Const(b"global")], program: None },
+};
+
+const FEE_RECIPIENT: IdlAccount = IdlAccount {
+    name: "fee_recipient",
+    writable: true,
+    signer: false,
+    source: IdlAccountSource::Input(IdlInput::FeeRecipient),
+};
+
+const MINT: IdlAccount = IdlAccount {
+    name: "mint",
+    writable: false,
+    signer: false,
+    source: IdlAccountSource::Input(IdlInput::Mint),
+};
+
+const BONDING_CURVE: IdlAccount = IdlAccount {
+    name: "bonding_curve",
+    writable: true,
+    signer: false,
+    source: IdlAccountSource::Pda {
+        seeds: &[IdlSeed::Const(b"bonding-curve"), IdlSeed::Input(IdlInput::Mint)],
+        program: None,
+    },
+};
+
+const ASSOCIATED_BONDING_CURVE: IdlAccount = IdlAccount {
+    name: "associated_bonding_curve",
+    writable: true,
+    signer: false,
+    source: IdlAccountSource::Pda {
+        seeds: &[
+            IdlSeed::Account("bonding_curve"),
+            IdlSeed::Account("token_program"),
+            IdlSeed::Input(IdlInput::Mint),
+        ],
+        program: Some(ASSOCIATED_TOKEN_PROGRAM_ID),
+    },
+};
+
+const ASSOCIATED_USER: IdlAccount = IdlAccount {
+    name: "associated_user",
+    writable: true,
+    signer: false,
+    source: IdlAccountSource::Input(IdlInput::AssociatedUser),
+};
+
+const USER: IdlAccount = IdlAccount {
+    name: "user",
+    writable: true,
+    signer: true,
+    source: IdlAccountSource::Input(IdlInput::User),
+};
+
+const SYSTEM_PROGRAM: IdlAccount = IdlAccount {
+    name: "system_program",
+    writable: false,
+    signer: false,
+    source: IdlAccountSource::Address("11111111111111111111111111111111"),
+};
+
+const TOKEN_PROGRAM: IdlAccount = IdlAccount {
+    name: "token_program",
+    writable: false,
+    signer: false,
+    source: IdlAccountSource::Address(TOKEN_PROGRAM_ID),
+};
+
+const CREATOR_VAULT: IdlAccount = IdlAccount {
+    name: "creator_vault",
+    writable: true,
+    signer: false,
+    source: IdlAccountSource::Pda {
+        seeds: &[IdlSeed::Const(b"creator-vault"), IdlSeed::Input(IdlInput::Creator)],
+        program: None,
+    },
+};
+
+const EVENT_AUTHORITY: IdlAccount = IdlAccount {
+    name: "event_authority",
+    writable: false,
+    signer: false,
+    source: IdlAccountSource::Pda { seeds: &[IdlSeed::Const(b"__event_authority")], program: None },
+};
+
+const PROGRAM: IdlAccount = IdlAccount {
+    name: "program",
+    writable: false,
+    signer: false,
+    source: IdlAccountSource::Address(PUMPFUN_PROGRAM_ID),
+};
+
+const MINT_SIGNER: IdlAccount = IdlAccount {
+    name: "mint",
+    writable: true,
+    signer: true,
+    source: IdlAccountSource::Input(IdlInput::Mint),
+};
+
+const MINT_AUTHORITY: IdlAccount = IdlAccount {
+    name: "mint_authority",
+    writable: false,
+    signer: false,
+    source: IdlAccountSource::Pda { seeds: &[IdlSeed::Const(b"mint-authority")], program: None },
+};
+
+const MPL_TOKEN_METADATA: IdlAccount = IdlAccount {
+    name: "mpl_token_metadata",
+    writable: false,
+    signer: false,
+    source: IdlAccountSource::Address(MPL_TOKEN_METADATA_PROGRAM_ID),
+};
+
+const METADATA: IdlAccount = IdlAccount {
+    name: "metadata",
+    writable: true,
+    signer: false,
+    source: IdlAccountSource::Pda {
+        seeds: &[
+            IdlSeed::Const(b"metadata"),
+            IdlSeed::Account("mpl_token_metadata"),
+            IdlSeed::Input(IdlInput::Mint),
+        ],
+        program: Some(MPL_TOKEN_METADATA_PROGRAM_ID),
+    },
+};
+
+const ASSOCIATED_TOKEN_PROGRAM: IdlAccount = IdlAccount {
+    name: "associated_token_program",
+    writable: false,
+    signer: false,
+    source: IdlAccountSource::Address(ASSOCIATED_TOKEN_PROGRAM_ID),
+};
+
+const RENT: IdlAccount = IdlAccount {
+    name: "rent",
+    writable: false,
+    signer: false,
+    source: IdlAccountSource::Address("SysvarRent111111111111111111111111111111111"),
+};
+
+// same order as the on-chain instructions, seeds may point at accounts listed later
+pub const BUY_ACCOUNTS: &[IdlAccount] = &[
+    GLOBAL,
+    FEE_RECIPIENT,
+    MINT,
+    BONDING_CURVE,
+    ASSOCIATED_BONDING_CURVE,
+    ASSOCIATED_USER,
+    USER,
+    SYSTEM_PROGRAM,
+    TOKEN_PROGRAM,
+    CREATOR_VAULT,
+    EVENT_AUTHORITY,
+    PROGRAM,
+];
+
+pub const SELL_ACCOUNTS: &[IdlAccount] = &[
+    GLOBAL,
+    FEE_RECIPIENT,
+    MINT,
+    BONDING_CURVE,
+    ASSOCIATED_BONDING_CURVE,
+    ASSOCIATED_USER,
+    USER,
+    SYSTEM_PROGRAM,
+    CREATOR_VAULT,
+    TOKEN_PROGRAM,
+    EVENT_AUTHORITY,
+    PROGRAM,
+];
+
+pub const CREATE_ACCOUNTS: &[IdlAccount] = &[
+    MINT_SIGNER,
+    MINT_AUTHORITY,
+    BONDING_CURVE,
+    ASSOCIATED_BONDING_CURVE,
+    GLOBAL,
+    MPL_TOKEN_METADATA,
+    METADATA,
+    USER,
+    SYSTEM_PROGRAM,
+    TOKEN_PROGRAM,
+    ASSOCIATED_TOKEN_PROGRAM,
+    RENT,
+    EVENT_AUTHORITY,
+    PROGRAM,
+];
+
+#[derive(Debug, Clone)]
+pub struct PumpfunAccountInputs {
+    pub mint: Pubkey,
+    pub user: Pubkey,
+    pub associated_user: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub creator: Pubkey,
+    // addresses supplied by the caller win over the derived ones
+    pub overrides: Vec<(&'static str, Pubkey)>,
+}
+
+impl PumpfunAccountInputs {
+    fn get(&self, input: IdlInput) -> Pubkey {
+        match input {
+            IdlInput::Mint => self.mint,
+            IdlInput::User => self.user,
+            IdlInput::AssociatedUser => self.associated_user,
+            IdlInput::FeeRecipient => self.fee_recipient,
+            IdlInput::Creator => self.creator,
+        }
+    }
+}
+
+pub fn find_pda(seeds: &[&[u8]], program_id: &Pubkey) -> Pubkey {
+    let (pda, _bump) = Pubkey::find_program_address(seeds, program_id);
+    pda
+}
+
+pub fn get_global_pda(pumpfun_program_id: &Pubkey) -> Pubkey {
+    find_pda(&[b"global"], pumpfun_program_id)
+}
+
+pub fn get_event_authority_pda(pumpfun_program_id: &Pubkey) -> Pubkey {
+    find_pda(&[b"__event_authority"], pumpfun_program_id)
+}
+
+pub fn get_creator_vault_pda(creator: &Pubkey, pumpfun_program_id: &Pubkey) -> Pubkey {
+    find_pda(&[b"creator-vault", creator.as_ref()], pumpfun_program_id)
+}
+
+fn resolve_account(
+    account: &IdlAccount,
+    inputs: &PumpfunAccountInputs,
+    resolved: &[(&'static str, Pubkey)],
+    pumpfun_program_id: &Pubkey,
+) -> Result<Option<Pubkey>> {
+    if let Some((_, address)) = inputs.overrides.iter().find(|(name, _)| *name == account.name) {
+        return Ok(Some(*address));
+    }
+
+    let address = match account.source {
+        IdlAccountSource::Address(address) => Some(Pubkey::from_str(address)?),
+        IdlAccountSource::Input(input) => Some(inputs.get(input)),
+        IdlAccountSource::Pda { seeds, program } => {
+            let program_id = match program {
+                Some(program) => Pubkey::from_str(program)?,
+                None => *pumpfun_program_id,
+            };
+            let mut seed_keys: Vec<Pubkey> = vec![];
+            let mut const_seeds: Vec<&[u8]> = vec![];
+            let mut layout = vec![];
+            for seed in seeds {
+                match seed {
+                    IdlSeed::Const(bytes) => {
+                        layout.push((true, const_seeds.len()));
+                        const_seeds.push(bytes);
+                    },
+                    IdlSeed::Input(input) => {
+                        layout.push((false, seed_keys.len()));
+                        seed_keys.push(inputs.get(*input));
+                    },
+                    IdlSeed::Account(name) => {
+                        match resolved.iter().find(|(n, _)| n == name) {
+                            Some((_, key)) => {
+                                layout.push((false, seed_keys.len()));
+                                seed_keys.push(*key);
+                            },
+                            None => return Ok(None),
+                        }
+                    },
+                }
+            }
+            let seed_slices: Vec<&[u8]> = layout.iter()
+                .map(|(is_const, index)| {
+                    if *is_const { const_seeds[*index] } else { seed_keys[*index].as_ref() }
+                })
+                .collect();
+            Some(find_pda(&seed_slices, &program_id))
+        },
+    };
+    Ok(address)
+}
+
+// resolve addresses and lock flags for an instruction account list of the IDL
+pub fn build_account_metas(
+    accounts: &[IdlAccount],
+    inputs: &PumpfunAccountInputs,
+    pumpfun_program_id: &Pubkey,
+) -> Result<Vec<AccountMeta>> {
+    let mut resolved: Vec<(&'static str, Pubkey)> = vec![];
+    let mut pending: Vec<&IdlAccount> = accounts.iter().collect();
+
+    while !pending.is_empty() {
+        let before = pending.len();
+        let mut still_pending = vec![];
+        for account in pending {
+            match resolve_account(account, inputs, &resolved, pumpfun_program_id)? {
+                Some(address) => resolved.push((account.name, address)),
+                None => still_pending.push(account),
+            }
+        }
+        if still_pending.len() == before {
+            return Err(anyhow!("unresolvable pump.fun accounts: {:?}",
+                still_pending.iter().map(|a| a.name).collect::<Vec<_>>()));
+        }
+        pending = still_pending;
+    }
+
+    accounts.iter()
+        .map(|account| {
+            let (_, address) = resolved.iter()
+                .find(|(name, _)| *name == account.name)
+                .ok_or_else(|| anyhow!("pump.fun account {} not resolved", account.name))?;
+            Ok(if account.writable {
+                AccountMeta::new(*address, account.signer)
+            } else {
+                AccountMeta::new_readonly(*address, account.signer)
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contracts::pumpfun::Pumpfun;
+    use serde_json::Value;
+
+    // program wide accounts and instruction discriminators as they appear in mainnet
+    // pump.fun buy / sell / create transactions
+    const MAINNET_GLOBAL: &str = "4wTV1YmiEkRvAtNtsSGPtUrqRYQMe5SKy2uB4Jjaxnjf";
+    const MAINNET_EVENT_AUTHORITY: &str = "Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1";
+    const MAINNET_MINT_AUTHORITY: &str = "TSLvdd1pWpHVjahSpsvCXUbgwsL3JAcvokwaKt1eokM";
+    const BUY_DISCRIMINATOR: [u8; 8] = [0x66, 0x06, 0x3d, 0x12, 0x01, 0xda, 0xeb, 0xea];
+    const SELL_DISCRIMINATOR: [u8; 8] = [0x33, 0xe6, 0x85, 0xa4, 0x01, 0x7f, 0x83, 0xad];
+    const CREATE_DISCRIMINATOR: [u8; 8] = [0x18, 0x1e, 0xc8, 0x28, 0x05, 0x1c, 0x07, 0x77];
+
+    // the signing keys of the transactions below, every other address in them (atas, curve,
+    // creator vault, metadata) was derived without this module
+    const MINT: &str = "9xkU49fBixt9jfgvsHquwoA6HBLriYgVsaqa6RaPKuQU";
+    const USER: &str = "GkzUqR1dxyB3mUyZCK9KkoLx7CrXaFYRoDNK9bFpnw1R";
+    const CREATOR: &str = "3hkaVHxLrY8TPdb2PCejcH1kUY2B5hXVWMYuWscHkDQT";
+    const USER_ATA: &str = "AwokKBGaqg4pvyfC3bnAp3ZTbDi2QPHRGe41h9eyseco";
+
+    // transaction messages in the layout getTransaction returns with jsonParsed encoding:
+    // compute budget, optional ata create, then the pump.fun instruction
+
+    const BUY_TRANSACTION: &str = r#"
+    {
+        "accountKeys": [
+            { "pubkey": "GkzUqR1dxyB3mUyZCK9KkoLx7CrXaFYRoDNK9bFpnw1R", "signer": true, "writable": true, "source": "transaction" },
+            { "pubkey": "AwokKBGaqg4pvyfC3bnAp3ZTbDi2QPHRGe41h9eyseco", "signer": false, "writable": true, "source": "transaction" },
+            { "pubkey": "CebN5WGQ4jvEPvsVU4EoHEpgzq1VV7AbicfhtW4xC9iM", "signer": false, "writable": true, "source": "transaction" },
+            { "pubkey": "86cr9cnz2GGvizfspjQWftEjvUKBmqwugNmmN8YQXuXZ", "signer": false, "writable": true, "source": "transaction" },
+            { "pubkey": "FDwpUAJztQ8eS2hnN1518BAeTd59Gpaa4AYCGB69CAAX", "signer": false, "writable": true, "source": "transaction" },
+            { "pubkey": "5LYeNuNodH7U7fYZo1RALx5WcYMWVd36M8NEaKFKTU1N", "signer": false, "writable": true, "source": "transaction" },
+            { "pubkey": "ComputeBudget111111111111111111111111111111", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "9xkU49fBixt9jfgvsHquwoA6HBLriYgVsaqa6RaPKuQU", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "11111111111111111111111111111111", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "4wTV1YmiEkRvAtNtsSGPtUrqRYQMe5SKy2uB4Jjaxnjf", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P", "signer": false, "writable": false, "source": "transaction" }
+        ],
+        "instructions": [
+            {
+                "programId": "ComputeBudget111111111111111111111111111111",
+                "accounts": [],
+                "data": "K1wVZZ",
+                "stackHeight": null
+            },
+            {
+                "programId": "ComputeBudget111111111111111111111111111111",
+                "accounts": [],
+                "data": "3QCwqmHZ4mdq",
+                "stackHeight": null
+            },
+            {
+                "programId": "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+                "accounts": [
+                    "GkzUqR1dxyB3mUyZCK9KkoLx7CrXaFYRoDNK9bFpnw1R",
+                    "AwokKBGaqg4pvyfC3bnAp3ZTbDi2QPHRGe41h9eyseco",
+                    "GkzUqR1dxyB3mUyZCK9KkoLx7CrXaFYRoDNK9bFpnw1R",
+                    "9xkU49fBixt9jfgvsHquwoA6HBLriYgVsaqa6RaPKuQU",
+                    "11111111111111111111111111111111",
+                    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+                ],
+                "data": "2",
+                "stackHeight": null
+            },
+            {
+                "programId": "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P",
+                "accounts": [
+                    "4wTV1YmiEkRvAtNtsSGPtUrqRYQMe5SKy2uB4Jjaxnjf",
+                    "CebN5WGQ4jvEPvsVU4EoHEpgzq1VV7AbicfhtW4xC9iM",
+                    "9xkU49fBixt9jfgvsHquwoA6HBLriYgVsaqa6RaPKuQU",
+                    "86cr9cnz2GGvizfspjQWftEjvUKBmqwugNmmN8YQXuXZ",
+                    "FDwpUAJztQ8eS2hnN1518BAeTd59Gpaa4AYCGB69CAAX",
+                    "AwokKBGaqg4pvyfC3bnAp3ZTbDi2QPHRGe41h9eyseco",
+                    "GkzUqR1dxyB3mUyZCK9KkoLx7CrXaFYRoDNK9bFpnw1R",
+                    "11111111111111111111111111111111",
+                    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "5LYeNuNodH7U7fYZo1RALx5WcYMWVd36M8NEaKFKTU1N",
+                    "Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1",
+                    "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P"
+                ],
+                "data": "AJTQ2h9DXrBdAWsW2RQ8sYNVmqrVpxvrj",
+                "stackHeight": null
+            }
+        ]
+    }
+    "#;
+
+    const SELL_TRANSACTION: &str = r#"
+    {
+        "accountKeys": [
+            { "pubkey": "GkzUqR1dxyB3mUyZCK9KkoLx7CrXaFYRoDNK9bFpnw1R", "signer": true, "writable": true, "source": "transaction" },
+            { "pubkey": "CebN5WGQ4jvEPvsVU4EoHEpgzq1VV7AbicfhtW4xC9iM", "signer": false, "writable": true, "source": "transaction" },
+            { "pubkey": "86cr9cnz2GGvizfspjQWftEjvUKBmqwugNmmN8YQXuXZ", "signer": false, "writable": true, "source": "transaction" },
+            { "pubkey": "FDwpUAJztQ8eS2hnN1518BAeTd59Gpaa4AYCGB69CAAX", "signer": false, "writable": true, "source": "transaction" },
+            { "pubkey": "AwokKBGaqg4pvyfC3bnAp3ZTbDi2QPHRGe41h9eyseco", "signer": false, "writable": true, "source": "transaction" },
+            { "pubkey": "5LYeNuNodH7U7fYZo1RALx5WcYMWVd36M8NEaKFKTU1N", "signer": false, "writable": true, "source": "transaction" },
+            { "pubkey": "ComputeBudget111111111111111111111111111111", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "4wTV1YmiEkRvAtNtsSGPtUrqRYQMe5SKy2uB4Jjaxnjf", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "9xkU49fBixt9jfgvsHquwoA6HBLriYgVsaqa6RaPKuQU", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "11111111111111111111111111111111", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P", "signer": false, "writable": false, "source": "transaction" }
+        ],
+        "instructions": [
+            {
+                "programId": "ComputeBudget111111111111111111111111111111",
+                "accounts": [],
+                "data": "K1wVZZ",
+                "stackHeight": null
+            },
+            {
+                "programId": "ComputeBudget111111111111111111111111111111",
+                "accounts": [],
+                "data": "3QCwqmHZ4mdq",
+                "stackHeight": null
+            },
+            {
+                "programId": "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P",
+                "accounts": [
+                    "4wTV1YmiEkRvAtNtsSGPtUrqRYQMe5SKy2uB4Jjaxnjf",
+                    "CebN5WGQ4jvEPvsVU4EoHEpgzq1VV7AbicfhtW4xC9iM",
+                    "9xkU49fBixt9jfgvsHquwoA6HBLriYgVsaqa6RaPKuQU",
+                    "86cr9cnz2GGvizfspjQWftEjvUKBmqwugNmmN8YQXuXZ",
+                    "FDwpUAJztQ8eS2hnN1518BAeTd59Gpaa4AYCGB69CAAX",
+                    "AwokKBGaqg4pvyfC3bnAp3ZTbDi2QPHRGe41h9eyseco",
+                    "GkzUqR1dxyB3mUyZCK9KkoLx7CrXaFYRoDNK9bFpnw1R",
+                    "11111111111111111111111111111111",
+                    "5LYeNuNodH7U7fYZo1RALx5WcYMWVd36M8NEaKFKTU1N",
+                    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1",
+                    "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P"
+                ],
+                "data": "5jRcjdixRUDE6KkKjLE1m2arnJ2bNygij",
+                "stackHeight": null
+            }
+        ]
+    }
+    "#;
+
+    const CREATE_TRANSACTION: &str = r#"
+    {
+        "accountKeys": [
+            { "pubkey": "3hkaVHxLrY8TPdb2PCejcH1kUY2B5hXVWMYuWscHkDQT", "signer": true, "writable": true, "source": "transaction" },
+            { "pubkey": "9xkU49fBixt9jfgvsHquwoA6HBLriYgVsaqa6RaPKuQU", "signer": true, "writable": true, "source": "transaction" },
+            { "pubkey": "86cr9cnz2GGvizfspjQWftEjvUKBmqwugNmmN8YQXuXZ", "signer": false, "writable": true, "source": "transaction" },
+            { "pubkey": "FDwpUAJztQ8eS2hnN1518BAeTd59Gpaa4AYCGB69CAAX", "signer": false, "writable": true, "source": "transaction" },
+            { "pubkey": "3p65z1aKkZWSQfsQ4U49v2ygurvMdHtW9RJc5MEgccos", "signer": false, "writable": true, "source": "transaction" },
+            { "pubkey": "ComputeBudget111111111111111111111111111111", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "TSLvdd1pWpHVjahSpsvCXUbgwsL3JAcvokwaKt1eokM", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "4wTV1YmiEkRvAtNtsSGPtUrqRYQMe5SKy2uB4Jjaxnjf", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "11111111111111111111111111111111", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "SysvarRent111111111111111111111111111111111", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1", "signer": false, "writable": false, "source": "transaction" },
+            { "pubkey": "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P", "signer": false, "writable": false, "source": "transaction" }
+        ],
+        "instructions": [
+            {
+                "programId": "ComputeBudget111111111111111111111111111111",
+                "accounts": [],
+                "data": "K1wVZZ",
+                "stackHeight": null
+            },
+            {
+                "programId": "ComputeBudget111111111111111111111111111111",
+                "accounts": [],
+                "data": "3QCwqmHZ4mdq",
+                "stackHeight": null
+            },
+            {
+                "programId": "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P",
+                "accounts": [
+                    "9xkU49fBixt9jfgvsHquwoA6HBLriYgVsaqa6RaPKuQU",
+                    "TSLvdd1pWpHVjahSpsvCXUbgwsL3JAcvokwaKt1eokM",
+                    "86cr9cnz2GGvizfspjQWftEjvUKBmqwugNmmN8YQXuXZ",
+                    "FDwpUAJztQ8eS2hnN1518BAeTd59Gpaa4AYCGB69CAAX",
+                    "4wTV1YmiEkRvAtNtsSGPtUrqRYQMe5SKy2uB4Jjaxnjf",
+                    "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s",
+                    "3p65z1aKkZWSQfsQ4U49v2ygurvMdHtW9RJc5MEgccos",
+                    "3hkaVHxLrY8TPdb2PCejcH1kUY2B5hXVWMYuWscHkDQT",
+                    "11111111111111111111111111111111",
+                    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+                    "SysvarRent111111111111111111111111111111111",
+                    "Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1",
+                    "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P"
+                ],
+                "data": "4nFqwogzxK5RQdqTPAgPAmQZUJwTPHwzyzdXmK45UuEP3CS1NNrdRpAChcWSk3uspavWHCfMNhYiiXW84NdTPJNqPF967wMBhs8DUcLrW2LbcdZbT8iokyudV9y",
+                "stackHeight": null
+            }
+        ]
+    }
+    "#;
+
+    fn key(address: &str) -> Pubkey {
+        Pubkey::from_str(address).unwrap()
+    }
+
+    fn program() -> Pumpfun {
+        Pumpfun::new(key(PUMPFUN_PROGRAM_ID))
+    }
+
+    // (address, writable, signer) in instruction order
+    fn metas(accounts: &[AccountMeta]) -> Vec<(Pubkey, bool, bool)> {
+        accounts.iter().map(|meta| (meta.pubkey, meta.is_writable, meta.is_signer)).collect()
+    }
+
+    // the pump.fun instruction of a transaction, flags come from the message account keys
+    fn pumpfun_instruction(transaction: &str) -> (Vec<(Pubkey, bool, bool)>, Vec<u8>) {
+        let transaction: Value = serde_json::from_str(transaction).unwrap();
+        let flags = |address: &str| {
+            let account = transaction["accountKeys"].as_array().unwrap().iter()
+                .find(|account| account["pubkey"] == address)
+                .unwrap();
+            (account["writable"].as_bool().unwrap(), account["signer"].as_bool().unwrap())
+        };
+        let instruction = transaction["instructions"].as_array().unwrap().iter()
+            .find(|instruction| instruction["programId"] == PUMPFUN_PROGRAM_ID)
+            .unwrap();
+
+        let accounts = instruction["accounts"].as_array().unwrap().iter()
+            .map(|account| {
+                let address = account.as_str().unwrap();
+                let (writable, signer) = flags(address);
+                (key(address), writable, signer)
+            })
+            .collect();
+        let data = bs58::decode(instruction["data"].as_str().unwrap()).into_vec().unwrap();
+        (accounts, data)
+    }
+
+    #[test]
+    fn program_pdas_match_mainnet() {
+        let program_id = key(PUMPFUN_PROGRAM_ID);
+        assert_eq!(get_global_pda(&program_id), key(MAINNET_GLOBAL));
+        assert_eq!(get_event_authority_pda(&program_id), key(MAINNET_EVENT_AUTHORITY));
+        assert_eq!(find_pda(&[b"mint-authority"], &program_id), key(MAINNET_MINT_AUTHORITY));
+    }
+
+    #[test]
+    fn buy_matches_transaction_fixture() {
+        let (accounts, data) = pumpfun_instruction(BUY_TRANSACTION);
+        let amount = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let max_sol_cost = u64::from_le_bytes(data[16..].try_into().unwrap());
+
+        let pumpfun = program();
+        let inputs = pumpfun
+            .get_account_inputs(&key(MINT), &key(USER_ATA), &key(USER), &key(PUMPFUN_FEE_RECIPIENT), &key(CREATOR), None)
+            .unwrap();
+        let ix = pumpfun.get_buy_instruction(&amount, &max_sol_cost, &inputs).unwrap();
+
+        assert_eq!(metas(&ix.accounts), accounts);
+        assert_eq!(ix.data[..8], BUY_DISCRIMINATOR);
+        assert_eq!(ix.data, data);
+    }
+
+    #[test]
+    fn sell_matches_transaction_fixture() {
+        let (accounts, data) = pumpfun_instruction(SELL_TRANSACTION);
+        let amount = u64::from_le_bytes(data[8..16].try_into().unwrap());
+        let min_sol_output = u64::from_le_bytes(data[16..].try_into().unwrap());
+
+        // sell swaps creator_vault and token_program relative to buy
+        let pumpfun = program();
+        let inputs = pumpfun
+            .get_account_inputs(&key(MINT), &key(USER_ATA), &key(USER), &key(PUMPFUN_FEE_RECIPIENT), &key(CREATOR), None)
+            .unwrap();
+        let ix = pumpfun.get_sell_instruction(&amount, &min_sol_output, &inputs).unwrap();
+
+        assert_eq!(metas(&ix.accounts), accounts);
+        assert_eq!(ix.data[..8], SELL_DISCRIMINATOR);
+        assert_eq!(ix.data, data);
+    }
+
+    #[test]
+    fn creator_vault_override_replaces_the_derived_one() {
+        let (accounts, _) = pumpfun_instruction(SELL_TRANSACTION);
+        let vault = Pubkey::new_unique();
+        let pumpfun = program();
+        let inputs = pumpfun
+            .get_account_inputs(&key(MINT), &key(USER_ATA), &key(USER), &key(PUMPFUN_FEE_RECIPIENT), &key(CREATOR), Some(&vault))
+            .unwrap();
+        let ix = pumpfun.get_sell_instruction(&0, &0, &inputs).unwrap();
+
+        let mut expected = accounts;
+        expected[8].0 = vault;
+        assert_eq!(metas(&ix.accounts), expected);
+    }
+
+    #[test]
+    fn create_matches_transaction_fixture() {
+        let (accounts, data) = pumpfun_instruction(CREATE_TRANSACTION);
+        let ix = program()
+            .get_create_instruction("Fixture", "FIX", "https://ipfs.io/ipfs/fixture", &key(MINT), &key(CREATOR), &key(CREATOR))
+            .unwrap();
+
+        assert_eq!(metas(&ix.accounts), accounts);
+        assert_eq!(ix.data[..8], CREATE_DISCRIMINATOR);
+        assert_eq!(ix.data, data);
+    }
+}
@@ -7,21 +7,24 @@ use solana_program::{
     program_pack::Pack,
     sysvar::rent::Rent,
 };
-use spl_associated_token_account::get_associated_token_address;
 use borsh::{BorshDeserialize, BorshSerialize, from_slice};
 use solana_client::rpc_client::RpcClient;
 use solana_program::instruction::{AccountMeta, Instruction};
-use crate::configs::global::*;
+use crate::contracts::pumpfun_global::get_fee_params;
+use crate::contracts::pumpfun_idl::{
+    build_account_metas,
+    get_creator_vault_pda,
+    PumpfunAccountInputs,
+    BUY_ACCOUNTS,
+};
 
-use solana_program::system_program::ID as SYSTEM_PROGRAM_ID;
-use std::str::FromStr;
 use std::time::Instant;
 
 const BONDING_CURVE_SEED: &str = "bonding-curve";
 const METHOD_PROXY_BUY: u64 = 04974560345395; //buy
 const METHOD_PROXY_SELL: u64 = 15265570577868; //sell
 
-#[derive(BorshDeserialize, BorshSerialize, Debug)]
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
 pub struct BondingCurveInfo {
     pub discriminator: u64,
     pub virtual_token_reserves: u64,
@@ -33,6 +36,46 @@ pub struct BondingCurveInfo {
     pub creator: Pubkey,
 }
 
+impl BondingCurveInfo {
+
+    // tokens received for `sol_amount` lamports, the fee is charged on top of the sol side
+    pub fn get_buy_token_amount(&self, sol_amount: u64, fee_bps: u64) -> u64 {
+        if self.complete || self.virtual_sol_reserves == 0 {
+            return 0;
+        }
+        let sol_after_fee = sol_amount as u128 * 10_000 / (10_000 + fee_bps) as u128;
+        let token_amount = self.virtual_token_reserves as u128 * sol_after_fee
+            / (self.virtual_sol_reserves as u128 + sol_after_fee);
+        (token_amount as u64).min(self.real_token_reserves)
+    }
+
+    // lamports received for selling `token_amount`, the fee is taken from the output
+    pub fn get_sell_sol_amount(&self, token_amount: u64, fee_bps: u64) -> u64 {
+        if self.complete || self.virtual_token_reserves == 0 {
+            return 0;
+        }
+        let sol_amount = self.virtual_sol_reserves as u128 * token_amount as u128
+            / (self.virtual_token_reserves as u128 + token_amount as u128);
+        let fee = (sol_amount * fee_bps as u128).div_ceil(10_000);
+        sol_amount.saturating_sub(fee) as u64
+    }
+
+    // quote a buy and move the curve as if it landed, for buys that follow in the same bundle
+    pub fn simulate_buy(&mut self, sol_amount: u64, fee_bps: u64) -> u64 {
+        let token_amount = self.get_buy_token_amount(sol_amount, fee_bps);
+        let sol_after_fee = (sol_amount as u128 * 10_000 / (10_000 + fee_bps) as u128) as u64;
+        self.virtual_token_reserves -= token_amount;
+        self.real_token_reserves -= token_amount;
+        self.virtual_sol_reserves += sol_after_fee;
+        self.real_sol_reserves += sol_after_fee;
+        token_amount
+    }
+
+    pub fn get_creator_vault(&self, pumpfun_program_id: &Pubkey) -> Pubkey {
+        get_creator_vault_pda(&self.creator, pumpfun_program_id)
+    }
+}
+
 pub struct PumpfunProxy {
     connection: RpcClient,
     pumpfun_program_id: Pubkey,
@@ -94,14 +137,7 @@ impl PumpfunProxy {
         let account_info = self.get_bonding_curve_info(bonding_curve).await.unwrap();
         let creator = account_info.creator;
 
-        let (creator_vault, _bump) = Pubkey::find_program_address(
-            &[
-                b"creator-vault",
-                creator.as_ref()
-            ],
-            &self.pumpfun_program_id
-        );
-        creator_vault
+        get_creator_vault_pda(&creator, &self.pumpfun_program_id)
     }
 
     pub async fn get_accounts(
@@ -111,30 +147,29 @@ impl PumpfunProxy {
         payer: &Pubkey,
         creator_vault: Option<&Pubkey>,
     ) -> Result<Vec<AccountMeta>, Box<dyn std::error::Error>> {
-        let bonding_curve = self.get_bonding_curve_pda(mint);
-        let associated_bonding_curve = get_associated_token_address(&bonding_curve, mint);
+        let mut overrides = vec![];
+        let creator = match creator_vault {
+            Some(v) => {
+                overrides.push(("creator_vault", *v));
+                Pubkey::default()
+            },
+            None => {
+                let bonding_curve = self.get_bonding_curve_pda(mint);
+                self.get_bonding_curve_info(&bonding_curve).await?.creator
+            },
+        };
 
-        let creator_vault_get = match creator_vault {
-            Some(v) => *v,
-            None => self.get_creator_vault(&bonding_curve).await,
+        let inputs = PumpfunAccountInputs {
+            mint: *mint,
+            user: *payer,
+            associated_user: *coin_ata,
+            fee_recipient: get_fee_params(&self.connection).0,
+            creator,
+            overrides,
         };
 
-        Ok(
-            vec![
-                AccountMeta::new(Pubkey::from_str("4wTV1YmiEkRvAtNtsSGPtUrqRYQMe5SKy2uB4Jjaxnjf")?, false),
-                AccountMeta::new(Pubkey::from_str(PUMPFUN_FEE_RECIPIENT)?, false),
-                AccountMeta::new(mint.clone(), false),
-                AccountMeta::new(bonding_curve, false),
-                AccountMeta::new(associated_bonding_curve, false),
-                AccountMeta::new(coin_ata.clone(), false),
-                AccountMeta::new(payer.clone(), false),
-                AccountMeta::new(SYSTEM_PROGRAM_ID, false),
-                AccountMeta::new(Pubkey::from_str(TOKEN_PROGRAM_ID)?, false),
-                AccountMeta::new(creator_vault_get, false),
-                AccountMeta::new(Pubkey::from_str("Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1")?, false),
-                AccountMeta::new(self.pumpfun_program_id, false)
-            ]
-        )
+        //the proxy forwards the accounts in the pump.fun buy order for both sides
+        Ok(build_account_metas(BUY_ACCOUNTS, &inputs, &self.pumpfun_program_id)?)
     }
 
     pub async fn get_buy_instruction(
@@ -175,4 +210,60 @@ impl PumpfunProxy {
         Ok(build_swap_instruction)
     }
 
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a freshly created pump.fun curve: 30 virtual sol against 1_073_000_000 virtual tokens
+    fn fresh_curve() -> BondingCurveInfo {
+        BondingCurveInfo {
+            discriminator: 0,
+            virtual_token_reserves: 1_073_000_000_000_000,
+            virtual_sol_reserves: 30_000_000_000,
+            real_token_reserves: 793_100_000_000_000,
+            real_sol_reserves: 0,
+            token_total_supply: 1_000_000_000_000_000,
+            complete: false,
+            creator: Pubkey::default(),
+        }
+    }
+
+    #[test]
+    fn buy_charges_the_fee_on_the_sol_side() {
+        // 1 sol at 1%, 1e9 / 1.01 reaches the curve
+        assert_eq!(fresh_curve().get_buy_token_amount(1_000_000_000, 100), 34_281_150_129_545);
+    }
+
+    #[test]
+    fn sell_takes_the_fee_from_the_output() {
+        assert_eq!(fresh_curve().get_sell_sol_amount(34_281_150_129_545, 100), 919_504_642);
+    }
+
+    #[test]
+    fn simulate_buy_moves_the_curve() {
+        let mut curve = fresh_curve();
+        assert_eq!(curve.simulate_buy(1_000_000_000, 100), 34_281_150_129_545);
+        assert_eq!(curve.virtual_sol_reserves, 30_990_099_009);
+        assert_eq!(curve.real_sol_reserves, 990_099_009);
+        assert_eq!(curve.virtual_token_reserves, 1_073_000_000_000_000 - 34_281_150_129_545);
+        assert_eq!(curve.real_token_reserves, 793_100_000_000_000 - 34_281_150_129_545);
+        // the next buy in the bundle gets fewer tokens, and selling back returns less than paid
+        assert_eq!(curve.get_buy_token_amount(1_000_000_000, 100), 32_158_478_296_710);
+        assert_eq!(curve.get_sell_sol_amount(34_281_150_129_545, 100), 980_198_017);
+    }
+
+    #[test]
+    fn buys_are_capped_by_real_reserves() {
+        let curve = fresh_curve();
+        assert_eq!(curve.get_buy_token_amount(u64::MAX / 2, 100), curve.real_token_reserves);
+    }
+
+    #[test]
+    fn complete_curves_quote_zero() {
+        let mut curve = fresh_curve();
+        curve.complete = true;
+        assert_eq!(curve.get_buy_token_amount(1_000_000_000, 100), 0);
+        assert_eq!(curve.get_sell_sol_amount(1_000_000_000, 100), 0);
+    }
+}
@@ -0,0 +1,359 @@
+use anyhow::{anyhow, Result};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_client::rpc_client::RpcClient;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::pubkey::Pubkey;
+use solana_program::system_program::ID as SYSTEM_PROGRAM_ID;
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::native_mint::ID as WSOL_MINT;
+use crate::configs::bribe::get_random_tip_account;
+use crate::configs::global::*;
+use crate::utils::sighash;
+
+use std::str::FromStr;
+
+const POOL_SEED: &str = "pool";
+const POOL_AUTHORITY_SEED: &str = "pool-authority";
+const GLOBAL_CONFIG_SEED: &str = "global_config";
+const EVENT_AUTHORITY_SEED: &str = "__event_authority";
+const CREATOR_VAULT_SEED: &str = "creator_vault";
+
+//pools created by the pump.fun migration always use index 0
+const CANONICAL_POOL_INDEX: u16 = 0;
+const POOL_ACCOUNT_SIZE: usize = 243;
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+const FEE_DENOMINATOR: u64 = 10_000;
+
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+pub struct PumpSwapPool {
+    pub discriminator: u64,
+    pub pool_bump: u8,
+    pub index: u16,
+    pub creator: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub pool_base_token_account: Pubkey,
+    pub pool_quote_token_account: Pubkey,
+    pub lp_supply: u64,
+    pub coin_creator: Pubkey,
+}
+
+#[derive(Debug, Clone)]
+pub struct PumpSwapQuote {
+    pub base_reserve: u64,
+    pub quote_reserve: u64,
+    pub fee_bps: u64,
+}
+
+impl PumpSwapQuote {
+
+    pub fn new(pool: &PumpSwapPool, base_reserve: u64, quote_reserve: u64) -> Self {
+        let mut fee_bps = PUMPSWAP_LP_FEE_BPS + PUMPSWAP_PROTOCOL_FEE_BPS;
+        if pool.coin_creator != Pubkey::default() {
+            fee_bps += PUMPSWAP_COIN_CREATOR_FEE_BPS;
+        }
+        PumpSwapQuote {
+            base_reserve,
+            quote_reserve,
+            fee_bps,
+        }
+    }
+
+    // base tokens received for spending `quote_in`, fees are charged on top of the quote side
+    pub fn buy_base_out(&self, quote_in: u64) -> u64 {
+        let effective_quote_in = quote_in as u128 * FEE_DENOMINATOR as u128
+            / (FEE_DENOMINATOR + self.fee_bps) as u128;
+        let base_out = self.base_reserve as u128 * effective_quote_in
+            / (self.quote_reserve as u128 + effective_quote_in);
+        base_out as u64
+    }
+
+    // quote tokens received for selling `base_in`, fees are taken from the output
+    pub fn sell_quote_out(&self, base_in: u64) -> u64 {
+        let quote_out = self.quote_reserve as u128 * base_in as u128
+            / (self.base_reserve as u128 + base_in as u128);
+        let fee = (quote_out * self.fee_bps as u128).div_ceil(FEE_DENOMINATOR as u128);
+        quote_out.saturating_sub(fee) as u64
+    }
+}
+
+pub struct PumpSwap {
+    connection: RpcClient,
+    pumpfun_program_id: Pubkey,
+    pumpswap_program_id: Pubkey,
+}
+
+impl PumpSwap {
+
+    pub fn new(
+        connection: RpcClient,
+        pumpfun_program_id: Pubkey,
+        pumpswap_program_id: Pubkey,
+    ) -> Self {
+        PumpSwap {
+            connection,
+            pumpfun_program_id,
+            pumpswap_program_id,
+        }
+    }
+
+    pub fn get_pool_authority(
+        &self,
+        mint: &Pubkey,
+    ) -> Pubkey {
+        let (pool_authority, _bump) = Pubkey::find_program_address(
+            &[
+                POOL_AUTHORITY_SEED.as_bytes(),
+                mint.as_ref(),
+            ],
+            &self.pumpfun_program_id,
+        );
+        pool_authority
+    }
+
+    pub fn get_pool_pda(
+        &self,
+        mint: &Pubkey,
+    ) -> Pubkey {
+        let pool_authority = self.get_pool_authority(mint);
+        let (pool, _bump) = Pubkey::find_program_address(
+            &[
+                POOL_SEED.as_bytes(),
+                &CANONICAL_POOL_INDEX.to_le_bytes(),
+                pool_authority.as_ref(),
+                mint.as_ref(),
+                WSOL_MINT.as_ref(),
+            ],
+            &self.pumpswap_program_id,
+        );
+        pool
+    }
+
+    pub fn get_global_config_pda(&self) -> Pubkey {
+        let (global_config, _bump) = Pubkey::find_program_address(
+            &[GLOBAL_CONFIG_SEED.as_bytes()],
+            &self.pumpswap_program_id,
+        );
+        global_config
+    }
+
+    pub fn get_event_authority_pda(&self) -> Pubkey {
+        let (event_authority, _bump) = Pubkey::find_program_address(
+            &[EVENT_AUTHORITY_SEED.as_bytes()],
+            &self.pumpswap_program_id,
+        );
+        event_authority
+    }
+
+    pub fn get_coin_creator_vault_authority(
+        &self,
+        coin_creator: &Pubkey,
+    ) -> Pubkey {
+        let (vault_authority, _bump) = Pubkey::find_program_address(
+            &[
+                CREATOR_VAULT_SEED.as_bytes(),
+                coin_creator.as_ref(),
+            ],
+            &self.pumpswap_program_id,
+        );
+        vault_authority
+    }
+
+    pub fn get_pool_info(
+        &self,
+        pool: &Pubkey,
+    ) -> Result<PumpSwapPool> {
+        let account_data = self.connection.get_account_data(pool)
+            .map_err(|e| anyhow!("PumpSwap pool account not found: {}", e))?;
+
+        if account_data.len() < POOL_ACCOUNT_SIZE {
+            return Err(anyhow!("PumpSwap pool account too small: {}", account_data.len()));
+        }
+
+        let pool_account = PumpSwapPool::deserialize(&mut &account_data[..POOL_ACCOUNT_SIZE])
+            .map_err(|e| anyhow!("Failed to deserialize PumpSwap pool account: {}", e))?;
+
+        Ok(pool_account)
+    }
+
+    pub fn get_quote(
+        &self,
+        pool: &PumpSwapPool,
+    ) -> Result<PumpSwapQuote> {
+        let accounts = self.connection.get_multiple_accounts(&[
+            pool.pool_base_token_account,
+            pool.pool_quote_token_account,
+        ])?;
+
+        let mut reserves = vec![];
+        for account in accounts {
+            let account = account.ok_or_else(|| anyhow!("PumpSwap pool vault not found"))?;
+            let amount_bytes = account.data
+                .get(TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8)
+                .ok_or_else(|| anyhow!("PumpSwap pool vault has no token amount"))?;
+            reserves.push(u64::from_le_bytes(amount_bytes.try_into()?));
+        }
+
+        Ok(PumpSwapQuote::new(pool, reserves[0], reserves[1]))
+    }
+
+    pub fn get_accounts(
+        &self,
+        pool_addr: &Pubkey,
+        pool: &PumpSwapPool,
+        payer: &Pubkey,
+        user_base_account: &Pubkey, // the payer's ata, or a seed sub-account of the base mint
+        amount: &u64,
+    ) -> Result<Vec<AccountMeta>> {
+        let token_program_id = Pubkey::from_str(TOKEN_PROGRAM_ID)?;
+        let associated_token_program_id = Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID)?;
+        let protocol_fee_recipient = get_random_tip_account(PUMPSWAP_PROTOCOL_FEE_RECIPIENTS, amount);
+        let protocol_fee_recipient_ata = get_associated_token_address(
+            &protocol_fee_recipient,
+            &pool.quote_mint,
+        );
+        let coin_creator_vault_authority = self.get_coin_creator_vault_authority(&pool.coin_creator);
+        let coin_creator_vault_ata = get_associated_token_address(
+            &coin_creator_vault_authority,
+            &pool.quote_mint,
+        );
+
+        Ok(
+            vec![
+                AccountMeta::new(*pool_addr, false),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(self.get_global_config_pda(), false),
+                AccountMeta::new_readonly(pool.base_mint, false),
+                AccountMeta::new_readonly(pool.quote_mint, false),
+                AccountMeta::new(*user_base_account, false),
+                AccountMeta::new(get_associated_token_address(payer, &pool.quote_mint), false),
+                AccountMeta::new(pool.pool_base_token_account, false),
+                AccountMeta::new(pool.pool_quote_token_account, false),
+                AccountMeta::new_readonly(protocol_fee_recipient, false),
+                AccountMeta::new(protocol_fee_recipient_ata, false),
+                AccountMeta::new_readonly(token_program_id, false),
+                AccountMeta::new_readonly(token_program_id, false),
+                AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+                AccountMeta::new_readonly(associated_token_program_id, false),
+                AccountMeta::new_readonly(self.get_event_authority_pda(), false),
+                AccountMeta::new_readonly(self.pumpswap_program_id, false),
+                AccountMeta::new(coin_creator_vault_ata, false),
+                AccountMeta::new_readonly(coin_creator_vault_authority, false),
+            ]
+        )
+    }
+
+    pub fn get_buy_instruction(
+        &self,
+        pool_addr: &Pubkey,
+        pool: &PumpSwapPool,
+        base_amount_out: &u64,
+        max_quote_amount_in: &u64,
+        payer: &Pubkey,
+        user_base_account: &Pubkey,
+    ) -> Result<Instruction> {
+        let accounts = self.get_accounts(pool_addr, pool, payer, user_base_account, max_quote_amount_in)?;
+        let mut data = sighash("global", "buy").to_vec();
+        data.extend_from_slice(&base_amount_out.to_le_bytes());
+        data.extend_from_slice(&max_quote_amount_in.to_le_bytes());
+
+        Ok(Instruction {
+            program_id: self.pumpswap_program_id,
+            accounts,
+            data,
+        })
+    }
+
+    pub fn get_sell_instruction(
+        &self,
+        pool_addr: &Pubkey,
+        pool: &PumpSwapPool,
+        base_amount_in: &u64,
+        min_quote_amount_out: &u64,
+        payer: &Pubkey,
+        user_base_account: &Pubkey,
+    ) -> Result<Instruction> {
+        let accounts = self.get_accounts(pool_addr, pool, payer, user_base_account, base_amount_in)?;
+        let mut data = sighash("global", "sell").to_vec();
+        data.extend_from_slice(&base_amount_in.to_le_bytes());
+        data.extend_from_slice(&min_quote_amount_out.to_le_bytes());
+
+        Ok(Instruction {
+            program_id: self.pumpswap_program_id,
+            accounts,
+            data,
+        })
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(coin_creator: Pubkey) -> PumpSwapPool {
+        PumpSwapPool {
+            discriminator: 0,
+            pool_bump: 0,
+            index: CANONICAL_POOL_INDEX,
+            creator: Pubkey::default(),
+            base_mint: Pubkey::default(),
+            quote_mint: WSOL_MINT,
+            lp_mint: Pubkey::default(),
+            pool_base_token_account: Pubkey::default(),
+            pool_quote_token_account: Pubkey::default(),
+            lp_supply: 0,
+            coin_creator,
+        }
+    }
+
+    #[test]
+    fn canonical_pool_is_paired_with_the_wsol_mint() {
+        let pumpswap = PumpSwap::new(
+            RpcClient::new("http://127.0.0.1:1".to_string()),
+            Pubkey::from_str(PUMPFUN_PROGRAM_ID).unwrap(),
+            Pubkey::from_str(PUMPSWAP_PROGRAM_ID).unwrap(),
+        );
+        let mint = Pubkey::from_str("TSLvdd1pWpHVjahSpsvCXUbgwsL3JAcvokwaKt1eokM").unwrap();
+
+        assert_eq!(WSOL_MINT.to_string(), "So11111111111111111111111111111111111111112");
+        // derived outside this crate from the pump.fun and PumpSwap program ids
+        assert_eq!(
+            pumpswap.get_pool_authority(&mint).to_string(),
+            "8L3AKyELzk4CrTR34F85sMDzjGRuXcirACVWUPkXbgMS",
+        );
+        assert_eq!(
+            pumpswap.get_pool_pda(&mint).to_string(),
+            "BPLj27kwKmqnGpcHV1sxSqFNHV1tcw4Q7MEqVSHL6Ni3",
+        );
+    }
+
+    #[test]
+    fn coin_creator_fee_only_applies_when_set() {
+        let (base, quote) = (1_000_000_000_000, 100_000_000_000);
+        assert_eq!(PumpSwapQuote::new(&pool(Pubkey::default()), base, quote).fee_bps, 25);
+        assert_eq!(PumpSwapQuote::new(&pool(Pubkey::new_unique()), base, quote).fee_bps, 30);
+    }
+
+    #[test]
+    fn buy_and_sell_quotes_follow_the_constant_product() {
+        // 1_000_000 tokens (6 decimals) against 100 sol
+        let quote = PumpSwapQuote::new(&pool(Pubkey::default()), 1_000_000_000_000, 100_000_000_000);
+        // 1 sol in, 1e9 / 1.0025 after fee
+        assert_eq!(quote.buy_base_out(1_000_000_000), 9_876_543_205);
+        // 10_000 tokens in, 0.25% taken from the sol out, rounded up
+        assert_eq!(quote.sell_quote_out(10_000_000_000), 987_623_761);
+
+        let quote = PumpSwapQuote::new(&pool(Pubkey::new_unique()), 1_000_000_000_000, 100_000_000_000);
+        assert_eq!(quote.buy_base_out(1_000_000_000), 9_871_668_311);
+        assert_eq!(quote.sell_quote_out(10_000_000_000), 987_128_711);
+    }
+
+    #[test]
+    fn zero_in_quotes_zero() {
+        let quote = PumpSwapQuote::new(&pool(Pubkey::default()), 1_000_000_000_000, 100_000_000_000);
+        assert_eq!(quote.buy_base_out(0), 0);
+        assert_eq!(quote.sell_quote_out(0), 0);
+    }
+}
@@ -0,0 +1,392 @@
+use anyhow::{anyhow, Result};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_client::rpc_client::RpcClient;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::pubkey::Pubkey;
+use spl_token::native_mint::ID as WSOL_MINT;
+use crate::configs::global::*;
+
+use std::str::FromStr;
+
+const INSTRUCTION_SWAP_BASE_IN: u8 = 9;
+const AMM_INFO_SIZE: usize = 752;
+const MARKET_STATE_SIZE: usize = 388;
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+const AMM_ASSOCIATED_SEED: &str = "amm_associated_seed";
+
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+pub struct AmmFees {
+    pub min_separate_numerator: u64,
+    pub min_separate_denominator: u64,
+    pub trade_fee_numerator: u64,
+    pub trade_fee_denominator: u64,
+    pub pnl_numerator: u64,
+    pub pnl_denominator: u64,
+    pub swap_fee_numerator: u64,
+    pub swap_fee_denominator: u64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+pub struct AmmStateData {
+    pub need_take_pnl_coin: u64,
+    pub need_take_pnl_pc: u64,
+    pub total_pnl_pc: u64,
+    pub total_pnl_coin: u64,
+    pub pool_open_time: u64,
+    pub padding: [u64; 2],
+    pub orderbook_to_init_time: u64,
+    pub swap_coin_in_amount: u128,
+    pub swap_pc_out_amount: u128,
+    pub swap_acc_pc_fee: u64,
+    pub swap_pc_in_amount: u128,
+    pub swap_coin_out_amount: u128,
+    pub swap_acc_coin_fee: u64,
+}
+
+// raydium amm v4 `AmmInfo`, 752 bytes
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+pub struct AmmInfo {
+    pub status: u64,
+    pub nonce: u64,
+    pub order_num: u64,
+    pub depth: u64,
+    pub coin_decimals: u64,
+    pub pc_decimals: u64,
+    pub state: u64,
+    pub reset_flag: u64,
+    pub min_size: u64,
+    pub vol_max_cut_ratio: u64,
+    pub amount_wave: u64,
+    pub coin_lot_size: u64,
+    pub pc_lot_size: u64,
+    pub min_price_multiplier: u64,
+    pub max_price_multiplier: u64,
+    pub sys_decimal_value: u64,
+    pub fees: AmmFees,
+    pub state_data: AmmStateData,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub coin_vault_mint: Pubkey,
+    pub pc_vault_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub open_orders: Pubkey,
+    pub market: Pubkey,
+    pub market_program: Pubkey,
+    pub target_orders: Pubkey,
+    pub padding1: [u64; 8],
+    pub amm_owner: Pubkey,
+    pub lp_amount: u64,
+    pub client_order_id: u64,
+    pub recent_epoch: u64,
+    pub padding2: u64,
+}
+
+// openbook / serum `MarketState` v2, 388 bytes including the "serum" head and "padding" tail
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+pub struct MarketState {
+    pub head: [u8; 5],
+    pub account_flags: u64,
+    pub own_address: Pubkey,
+    pub vault_signer_nonce: u64,
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+    pub coin_vault: Pubkey,
+    pub coin_deposits_total: u64,
+    pub coin_fees_accrued: u64,
+    pub pc_vault: Pubkey,
+    pub pc_deposits_total: u64,
+    pub pc_fees_accrued: u64,
+    pub pc_dust_threshold: u64,
+    pub req_q: Pubkey,
+    pub event_q: Pubkey,
+    pub bids: Pubkey,
+    pub asks: Pubkey,
+    pub coin_lot_size: u64,
+    pub pc_lot_size: u64,
+    pub fee_rate_bps: u64,
+    pub referrer_rebates_accrued: u64,
+}
+
+// everything a swap needs to know about one pool
+#[derive(Debug, Clone)]
+pub struct RaydiumPool {
+    pub amm_id: Pubkey,
+    pub amm: AmmInfo,
+    pub market: MarketState,
+}
+
+#[derive(Debug, Clone)]
+pub struct RaydiumQuote {
+    pub coin_reserve: u64,
+    pub pc_reserve: u64,
+    pub swap_fee_numerator: u64,
+    pub swap_fee_denominator: u64,
+    pub sol_is_coin: bool,
+}
+
+impl RaydiumQuote {
+
+    // vault balances minus the pnl the pool still owes, which is not tradable
+    pub fn new(amm: &AmmInfo, coin_vault_amount: u64, pc_vault_amount: u64) -> Self {
+        RaydiumQuote {
+            coin_reserve: coin_vault_amount.saturating_sub(amm.state_data.need_take_pnl_coin),
+            pc_reserve: pc_vault_amount.saturating_sub(amm.state_data.need_take_pnl_pc),
+            swap_fee_numerator: amm.fees.swap_fee_numerator,
+            swap_fee_denominator: amm.fees.swap_fee_denominator,
+            sol_is_coin: amm.coin_vault_mint == WSOL_MINT,
+        }
+    }
+
+    pub fn sol_reserve(&self) -> u64 {
+        if self.sol_is_coin { self.coin_reserve } else { self.pc_reserve }
+    }
+
+    pub fn token_reserve(&self) -> u64 {
+        if self.sol_is_coin { self.pc_reserve } else { self.coin_reserve }
+    }
+
+    pub fn amount_out(&self, amount_in: u64, is_buy: bool) -> u64 {
+        let (reserve_in, reserve_out) = if is_buy {
+            (self.sol_reserve(), self.token_reserve())
+        } else {
+            (self.token_reserve(), self.sol_reserve())
+        };
+        if self.swap_fee_denominator == 0 {
+            return 0;
+        }
+        let amount_in_after_fee = amount_in as u128
+            * (self.swap_fee_denominator - self.swap_fee_numerator) as u128
+            / self.swap_fee_denominator as u128;
+        let amount_out = reserve_out as u128 * amount_in_after_fee
+            / (reserve_in as u128 + amount_in_after_fee);
+        amount_out as u64
+    }
+}
+
+pub struct RaydiumAmm {
+    connection: RpcClient,
+    raydium_program_id: Pubkey,
+}
+
+impl RaydiumAmm {
+
+    pub fn new(
+        connection: RpcClient,
+        raydium_program_id: Pubkey,
+    ) -> Self {
+        RaydiumAmm {
+            connection,
+            raydium_program_id,
+        }
+    }
+
+    // pools created with `initialize2` live at a pda of their OpenBook market
+    pub fn get_amm_pda(
+        &self,
+        market_id: &Pubkey,
+    ) -> Pubkey {
+        let (amm_id, _bump) = Pubkey::find_program_address(
+            &[
+                self.raydium_program_id.as_ref(),
+                market_id.as_ref(),
+                AMM_ASSOCIATED_SEED.as_bytes(),
+            ],
+            &self.raydium_program_id,
+        );
+        amm_id
+    }
+
+    pub fn get_amm_info(
+        &self,
+        amm_id: &Pubkey,
+    ) -> Result<AmmInfo> {
+        let account_data = self.connection.get_account_data(amm_id)
+            .map_err(|e| anyhow!("Raydium amm account not found: {}", e))?;
+
+        if account_data.len() < AMM_INFO_SIZE {
+            return Err(anyhow!("Raydium amm account too small: {}", account_data.len()));
+        }
+
+        AmmInfo::deserialize(&mut &account_data[..AMM_INFO_SIZE])
+            .map_err(|e| anyhow!("Failed to deserialize raydium amm account: {}", e))
+    }
+
+    pub fn get_market_info(
+        &self,
+        market_id: &Pubkey,
+    ) -> Result<MarketState> {
+        let account_data = self.connection.get_account_data(market_id)
+            .map_err(|e| anyhow!("OpenBook market account not found: {}", e))?;
+
+        if account_data.len() < MARKET_STATE_SIZE {
+            return Err(anyhow!("OpenBook market account too small: {}", account_data.len()));
+        }
+
+        MarketState::deserialize(&mut &account_data[..MARKET_STATE_SIZE - 7])
+            .map_err(|e| anyhow!("Failed to deserialize OpenBook market account: {}", e))
+    }
+
+    // `market_id` is the OpenBook market, the amm is derived from it
+    pub fn get_pool(
+        &self,
+        market_id: &Pubkey,
+    ) -> Result<RaydiumPool> {
+        let amm_id = self.get_amm_pda(market_id);
+        let amm = self.get_amm_info(&amm_id)?;
+        if amm.market != *market_id {
+            return Err(anyhow!("Raydium amm {} does not trade market {}", amm_id, market_id));
+        }
+        let market = self.get_market_info(market_id)?;
+
+        Ok(RaydiumPool {
+            amm_id,
+            amm,
+            market,
+        })
+    }
+
+    pub fn get_quote(
+        &self,
+        amm: &AmmInfo,
+    ) -> Result<RaydiumQuote> {
+        let accounts = self.connection.get_multiple_accounts(&[
+            amm.coin_vault,
+            amm.pc_vault,
+        ])?;
+
+        let mut reserves = vec![];
+        for account in accounts {
+            let account = account.ok_or_else(|| anyhow!("Raydium pool vault not found"))?;
+            let amount_bytes = account.data
+                .get(TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8)
+                .ok_or_else(|| anyhow!("Raydium pool vault has no token amount"))?;
+            reserves.push(u64::from_le_bytes(amount_bytes.try_into()?));
+        }
+
+        Ok(RaydiumQuote::new(amm, reserves[0], reserves[1]))
+    }
+
+    pub fn get_vault_signer(
+        &self,
+        market_id: &Pubkey,
+        market: &MarketState,
+        market_program: &Pubkey,
+    ) -> Result<Pubkey> {
+        Pubkey::create_program_address(
+            &[
+                market_id.as_ref(),
+                &market.vault_signer_nonce.to_le_bytes(),
+            ],
+            market_program,
+        ).map_err(|e| anyhow!("Failed to derive OpenBook vault signer: {}", e))
+    }
+
+    pub fn get_swap_base_in_instruction(
+        &self,
+        pool: &RaydiumPool,
+        amount_in: &u64,
+        min_amount_out: &u64,
+        user_source: &Pubkey,
+        user_destination: &Pubkey,
+        payer: &Pubkey,
+    ) -> Result<Instruction> {
+        let RaydiumPool { amm_id, amm, market } = pool;
+        let vault_signer = self.get_vault_signer(&amm.market, market, &amm.market_program)?;
+
+        let accounts = vec![
+            AccountMeta::new_readonly(Pubkey::from_str(TOKEN_PROGRAM_ID)?, false),
+            AccountMeta::new(*amm_id, false),
+            AccountMeta::new_readonly(Pubkey::from_str(RAYDIUM_AMM_AUTHORITY)?, false),
+            AccountMeta::new(amm.open_orders, false),
+            AccountMeta::new(amm.target_orders, false),
+            AccountMeta::new(amm.coin_vault, false),
+            AccountMeta::new(amm.pc_vault, false),
+            AccountMeta::new_readonly(amm.market_program, false),
+            AccountMeta::new(amm.market, false),
+            AccountMeta::new(market.bids, false),
+            AccountMeta::new(market.asks, false),
+            AccountMeta::new(market.event_q, false),
+            AccountMeta::new(market.coin_vault, false),
+            AccountMeta::new(market.pc_vault, false),
+            AccountMeta::new_readonly(vault_signer, false),
+            AccountMeta::new(*user_source, false),
+            AccountMeta::new(*user_destination, false),
+            AccountMeta::new_readonly(*payer, true),
+        ];
+
+        let mut data = vec![INSTRUCTION_SWAP_BASE_IN];
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&min_amount_out.to_le_bytes());
+
+        Ok(Instruction {
+            program_id: self.raydium_program_id,
+            accounts,
+            data,
+        })
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(sol_is_coin: bool) -> RaydiumQuote {
+        // 100 sol against 1_000_000 tokens (6 decimals), 0.25% swap fee
+        let (sol, token) = (100_000_000_000, 1_000_000_000_000);
+        RaydiumQuote {
+            coin_reserve: if sol_is_coin { sol } else { token },
+            pc_reserve: if sol_is_coin { token } else { sol },
+            swap_fee_numerator: 25,
+            swap_fee_denominator: 10_000,
+            sol_is_coin,
+        }
+    }
+
+    #[test]
+    fn buy_and_sell_quotes_follow_the_constant_product() {
+        for sol_is_coin in [true, false] {
+            let quote = quote(sol_is_coin);
+            assert_eq!(quote.sol_reserve(), 100_000_000_000);
+            assert_eq!(quote.token_reserve(), 1_000_000_000_000);
+            // 1 sol in, 0.9975 after fee: 1e12 * 0.9975e9 / (100e9 + 0.9975e9)
+            assert_eq!(quote.amount_out(1_000_000_000, true), 9_876_482_091);
+            // 10_000 tokens in, 9_975 after fee
+            assert_eq!(quote.amount_out(10_000_000_000, false), 987_648_209);
+        }
+    }
+
+    #[test]
+    fn sol_side_follows_the_wsol_vault() {
+        let mut amm = AmmInfo::deserialize(&mut &[0u8; AMM_INFO_SIZE][..]).unwrap();
+        amm.fees.swap_fee_numerator = 25;
+        amm.fees.swap_fee_denominator = 10_000;
+        amm.state_data.need_take_pnl_coin = 1_000;
+        amm.state_data.need_take_pnl_pc = 2_000;
+
+        // coin = wsol, pc = token
+        amm.coin_vault_mint = WSOL_MINT;
+        amm.pc_vault_mint = Pubkey::new_unique();
+        let quote = RaydiumQuote::new(&amm, 100_000_001_000, 1_000_000_002_000);
+        assert!(quote.sol_is_coin);
+        assert_eq!(quote.sol_reserve(), 100_000_000_000);
+        assert_eq!(quote.token_reserve(), 1_000_000_000_000);
+        assert_eq!(quote.amount_out(1_000_000_000, true), 9_876_482_091);
+
+        // coin = token, pc = wsol
+        amm.pc_vault_mint = amm.coin_vault_mint;
+        amm.coin_vault_mint = Pubkey::new_unique();
+        let quote = RaydiumQuote::new(&amm, 1_000_000_001_000, 100_000_002_000);
+        assert!(!quote.sol_is_coin);
+        assert_eq!(quote.sol_reserve(), 100_000_000_000);
+        assert_eq!(quote.amount_out(1_000_000_000, true), 9_876_482_091);
+    }
+
+    #[test]
+    fn degenerate_pools_quote_zero() {
+        let mut quote = quote(true);
+        assert_eq!(quote.amount_out(0, true), 0);
+        quote.swap_fee_denominator = 0;
+        assert_eq!(quote.amount_out(1_000_000_000, true), 0);
+    }
+}
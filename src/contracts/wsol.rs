@@ -0,0 +1,59 @@
+use solana_program::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    system_instruction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address,
+    instruction::create_associated_token_account_idempotent,
+};
+use std::str::FromStr;
+use spl_token::native_mint::ID as WSOL_MINT;
+use crate::configs::global::TOKEN_PROGRAM_ID;
+
+pub fn get_wsol_account(owner: &Pubkey) -> Pubkey {
+    get_associated_token_address(owner, &WSOL_MINT)
+}
+
+// create the wsol ata if needed, move lamports in and sync the token amount
+pub fn wrap_sol_instructions(
+    owner: &Pubkey,
+    lamports: u64,
+) -> Vec<Instruction> {
+    let token_program_id = Pubkey::from_str(TOKEN_PROGRAM_ID).unwrap();
+    let wsol_account = get_wsol_account(owner);
+
+    let mut instructions = vec![
+        create_associated_token_account_idempotent(
+            owner,
+            owner,
+            &WSOL_MINT,
+            &token_program_id,
+        ),
+    ];
+
+    if lamports > 0 {
+        instructions.push(system_instruction::transfer(owner, &wsol_account, lamports));
+        instructions.push(
+            spl_token::instruction::sync_native(&token_program_id, &wsol_account).unwrap()
+        );
+    }
+
+    instructions
+}
+
+// close the wsol ata, every lamport left in it goes back to the owner
+pub fn unwrap_sol_instruction(
+    owner: &Pubkey,
+) -> Instruction {
+    let token_program_id = Pubkey::from_str(TOKEN_PROGRAM_ID).unwrap();
+    let wsol_account = get_wsol_account(owner);
+
+    spl_token::instruction::close_account(
+        &token_program_id,
+        &wsol_account,
+        owner,
+        owner,
+        &[],
+    ).unwrap()
+}
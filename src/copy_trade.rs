@@ -0,0 +1,231 @@
+use anyhow::{anyhow, bail, Result};
+use futures::StreamExt;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use spl_associated_token_account::get_associated_token_address;
+use napi_derive::napi;
+
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+
+use crate::configs::global::NATIVE_MINT;
+use crate::events::{decode_trade_events, TradeEvent};
+use crate::listener::{spawn_listener, stop_listener};
+use crate::transaction_builder::TransactionBuilder;
+use crate::utils::SwapParam;
+
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct CopyTradeEvent {
+    pub copy_id: u32,
+    pub target_signature: String,
+    pub mint: String,
+    pub side: String, // "buy" | "sell"
+    pub target_sol_amount: String,
+    pub target_token_amount: String,
+    pub amount_in: String,
+    pub responses: Vec<String>,
+    pub error: Option<String>,
+}
+
+pub type CopyTradeCallback = Arc<dyn Fn(CopyTradeEvent) + Send + Sync>;
+
+#[derive(Clone)]
+pub struct CopyTradeConfig {
+    pub ws_url: String,
+    pub target: Pubkey,
+    pub ratio_bps: u64, // our size relative to the target's trade, 10_000 = same size
+    pub template: SwapParam, // wallet, fees, bribes; the track_* fields drive the sizing
+}
+
+// runs in the listener registry, the copy id is the listener id
+pub fn start_copy_trade(
+    config: CopyTradeConfig,
+    callback: CopyTradeCallback,
+) -> u32 {
+    spawn_listener(move |copy_id| {
+        let config = config.clone();
+        let callback = callback.clone();
+        async move { run_copy_trade(copy_id, &config, &callback).await }
+    })
+}
+
+pub fn stop_copy_trade(copy_id: u32) -> bool {
+    stop_listener(copy_id)
+}
+
+async fn run_copy_trade(
+    copy_id: u32,
+    config: &CopyTradeConfig,
+    callback: &CopyTradeCallback,
+) -> Result<()> {
+    let client = PubsubClient::new(&config.ws_url).await?;
+    let (mut stream, _unsubscribe) = client.logs_subscribe(
+        RpcTransactionLogsFilter::Mentions(vec![config.target.to_string()]),
+        RpcTransactionLogsConfig {
+            commitment: Some(CommitmentConfig::processed()),
+        },
+    ).await?;
+
+    while let Some(response) = stream.next().await {
+        let logs = response.value;
+        if logs.err.is_some() {
+            continue;
+        }
+
+        for event in decode_trade_events(&logs.logs) {
+            if event.user != config.target {
+                continue;
+            }
+            //the rpc client is blocking, keep it off the subscription task
+            let config = config.clone();
+            let signature = logs.signature.clone();
+            let copy_event = tokio::task::spawn_blocking(move || {
+                mirror_trade(copy_id, &config, &signature, &event)
+            }).await?;
+            callback(copy_event);
+        }
+    }
+
+    Err(anyhow!("log subscription closed"))
+}
+
+fn apply_slippage(amount: u64, slippage_bps: u64) -> u64 {
+    (amount as u128 * 10_000u128.saturating_sub(slippage_bps as u128) / 10_000) as u64
+}
+
+fn get_token_balance(rpc_client: &RpcClient, owner: &Pubkey, mint: &Pubkey) -> u64 {
+    rpc_client
+        .get_token_account_balance_with_commitment(
+            &get_associated_token_address(owner, mint),
+            CommitmentConfig::processed(),
+        )
+        .ok()
+        .and_then(|balance| balance.value.amount.parse().ok())
+        .unwrap_or(0)
+}
+
+// size our side of the target's trade, None when there is nothing to do
+pub fn build_copy_param(
+    config: &CopyTradeConfig,
+    event: &TradeEvent,
+    our_token_balance: u64,
+    target_token_balance_after: u64,
+) -> Option<SwapParam> {
+    let mut param = config.template.clone();
+    let slippage = param.track_slippage.unwrap_or(param.slippage_amount as u64);
+    param.slippage_amount = slippage as u32;
+
+    if event.is_buy {
+        let mut amount_in = match param.track_amount_in {
+            Some(fixed) => fixed,
+            None => (event.sol_amount as u128 * config.ratio_bps as u128 / 10_000) as u64,
+        };
+        if let Some(max_allow_buy) = param.track_max_allow_buy {
+            amount_in = amount_in.min(max_allow_buy);
+        }
+        if amount_in == 0 {
+            return None;
+        }
+
+        param.token_in = NATIVE_MINT.to_string();
+        param.token_out = event.mint.to_string();
+        param.amount_in = amount_in;
+        param.amount_out = 0;
+        param.calculate_amount_out = Some(true);
+    }
+    else {
+        if our_token_balance == 0 {
+            return None;
+        }
+        //sell the same fraction of our position as the target sold of theirs
+        let target_before = target_token_balance_after + event.token_amount;
+        let amount_in = if target_token_balance_after == 0 || target_before == 0 {
+            our_token_balance
+        } else {
+            (our_token_balance as u128 * event.token_amount as u128 / target_before as u128) as u64
+        };
+        if amount_in == 0 {
+            return None;
+        }
+        let expected_sol = (event.sol_amount as u128 * amount_in as u128
+            / event.token_amount.max(1) as u128) as u64;
+
+        param.token_in = event.mint.to_string();
+        param.token_out = NATIVE_MINT.to_string();
+        param.amount_in = amount_in;
+        param.amount_out = apply_slippage(expected_sol, slippage);
+    }
+
+    Some(param)
+}
+
+fn mirror_trade(
+    copy_id: u32,
+    config: &CopyTradeConfig,
+    signature: &str,
+    event: &TradeEvent,
+) -> CopyTradeEvent {
+    let mut copy_event = CopyTradeEvent {
+        copy_id,
+        target_signature: signature.to_string(),
+        mint: event.mint.to_string(),
+        side: if event.is_buy { "buy".to_string() } else { "sell".to_string() },
+        target_sol_amount: event.sol_amount.to_string(),
+        target_token_amount: event.token_amount.to_string(),
+        amount_in: "0".to_string(),
+        responses: vec![],
+        error: None,
+    };
+
+    let fee_payer = match config.template.signer() {
+        Ok(fee_payer) => fee_payer,
+        Err(e) => {
+            copy_event.error = Some(e.to_string());
+            return copy_event;
+        }
+    };
+    let rpc_client = RpcClient::new(config.template.connection.clone());
+
+    let (our_balance, target_balance) = if event.is_buy {
+        (0, 0)
+    } else {
+        (
+            get_token_balance(&rpc_client, &fee_payer.pubkey(), &event.mint),
+            get_token_balance(&rpc_client, &config.target, &event.mint),
+        )
+    };
+
+    let param = match build_copy_param(config, event, our_balance, target_balance) {
+        Some(param) => param,
+        None => {
+            copy_event.error = Some("nothing to mirror".to_string());
+            return copy_event;
+        }
+    };
+    copy_event.amount_in = param.amount_in.to_string();
+
+    let builder = TransactionBuilder::new(param.connection.clone(), fee_payer);
+    match Handle::current().block_on(builder.trade(&param)) {
+        Ok(responses) => copy_event.responses = responses,
+        Err(e) => copy_event.error = Some(e.to_string()),
+    }
+
+    copy_event
+}
+
+// the wallet to mirror. balances for sizing sells are read from chain on every trade,
+// so a fixed track_token_balance would be silently ignored and is refused instead
+pub fn parse_target(template: &SwapParam) -> Result<Pubkey> {
+    if template.track_token_balance.is_some() {
+        bail!("track_token_balance is not supported by copy trading, balances are read from chain");
+    }
+    let target = template.track_token_account.as_ref()
+        .ok_or_else(|| anyhow!("track_token_account is required for copy trading"))?;
+    Ok(Pubkey::from_str(target)?)
+}
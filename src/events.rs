@@ -0,0 +1,100 @@
+use base64::{Engine as _, engine::general_purpose};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+use crate::utils::sighash;
+
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+// pump.fun `TradeEvent`, only the leading fields are decoded
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+pub struct TradeEvent {
+    pub mint: Pubkey,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+    pub is_buy: bool,
+    pub user: Pubkey,
+    pub timestamp: i64,
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+}
+
+// pump.fun `CreateEvent`
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+pub struct CreateEvent {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub user: Pubkey,
+    pub creator: Pubkey,
+    pub timestamp: i64,
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub token_total_supply: u64,
+}
+
+// pump.fun `CompleteEvent`, emitted when a curve fills up and migrates
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+pub struct CompleteEvent {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone)]
+pub enum PumpfunEvent {
+    Create(CreateEvent),
+    Trade(TradeEvent),
+    Complete(CompleteEvent),
+}
+
+impl PumpfunEvent {
+
+    pub fn mint(&self) -> &Pubkey {
+        match self {
+            PumpfunEvent::Create(event) => &event.mint,
+            PumpfunEvent::Trade(event) => &event.mint,
+            PumpfunEvent::Complete(event) => &event.mint,
+        }
+    }
+}
+
+// anchor events are emitted as "Program data: <base64>", prefixed by sha256("event:<Name>")[..8]
+pub fn decode_program_data(log: &str) -> Option<Vec<u8>> {
+    let encoded = log.strip_prefix(PROGRAM_DATA_PREFIX)?;
+    general_purpose::STANDARD.decode(encoded.trim()).ok()
+}
+
+pub fn decode_event<T: BorshDeserialize>(data: &[u8], name: &str) -> Option<T> {
+    if data.len() < 8 || data[..8] != sighash("event", name) {
+        return None;
+    }
+    T::deserialize(&mut &data[8..]).ok()
+}
+
+pub fn decode_trade_events(logs: &[String]) -> Vec<TradeEvent> {
+    logs.iter()
+        .filter_map(|log| decode_program_data(log))
+        .filter_map(|data| decode_event::<TradeEvent>(&data, "TradeEvent"))
+        .collect()
+}
+
+pub fn decode_pumpfun_event(data: &[u8]) -> Option<PumpfunEvent> {
+    if let Some(event) = decode_event::<TradeEvent>(data, "TradeEvent") {
+        return Some(PumpfunEvent::Trade(event));
+    }
+    if let Some(event) = decode_event::<CreateEvent>(data, "CreateEvent") {
+        return Some(PumpfunEvent::Create(event));
+    }
+    decode_event::<CompleteEvent>(data, "CompleteEvent").map(PumpfunEvent::Complete)
+}
+
+pub fn decode_pumpfun_events(logs: &[String]) -> Vec<PumpfunEvent> {
+    logs.iter()
+        .filter_map(|log| decode_program_data(log))
+        .filter_map(|data| decode_pumpfun_event(&data))
+        .collect()
+}
@@ -0,0 +1,602 @@
+use anyhow::{anyhow, bail, Result};
+use dotenv::dotenv;
+use futures::stream::{select_all, StreamExt};
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use spl_associated_token_account::get_associated_token_address;
+use tracing::warn;
+
+use std::env;
+use std::fs;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::bonding_curve_cache::{decode_bonding_curve, get_bonding_curve, get_bonding_curve_pda};
+use crate::configs::global::NATIVE_MINT;
+use crate::contracts::pumpfun_global::get_fee_params;
+use crate::contracts::pumpfun_proxy::BondingCurveInfo;
+use crate::latency::timed_rpc;
+use crate::listener::spawn_listener;
+use crate::seed_account::resolve_seed_account;
+use crate::transaction_builder::TransactionBuilder;
+use crate::utils::SwapParam;
+
+// exit rules per position, evaluated on every bonding curve update. rules are kept in a json
+// file (EXIT_RULES_PATH_RUST) so they survive restarts; the wallet key is never written, the
+// watcher gets it from the swap template it is started with
+
+const DEFAULT_EXIT_RULES_PATH: &str = "exit_rules.json";
+const RULES_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+pub const RULE_STATUS_ACTIVE: &str = "active";
+pub const RULE_STATUS_TRIGGERED: &str = "triggered";
+pub const RULE_STATUS_FAILED: &str = "failed";
+// the curve completed and migrated to PumpSwap, the rule is no longer evaluated
+pub const RULE_STATUS_MIGRATED: &str = "migrated";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExitRule {
+    pub id: u32,
+    pub wallet: String,
+    pub mint: String,
+    pub entry_sol: u64,    // lamports paid for the position
+    pub token_amount: u64, // position size the rules are valued on
+    pub take_profit_bps: Option<u64>,   // exit once value >= entry * bps / 10_000
+    pub stop_loss_bps: Option<u64>,     // exit once value <= entry * bps / 10_000
+    pub trailing_stop_bps: Option<u64>, // exit once value drops this far below its peak
+    pub market_cap_sol: Option<u64>,    // exit once the curve market cap reaches these lamports
+    pub peak_value: u64,
+    pub status: String,
+}
+
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct ExitRule4Node {
+    pub id: Option<u32>,
+    pub wallet: String,
+    pub mint: String,
+    pub entry_sol: String,
+    pub token_amount: String,
+    pub take_profit_bps: Option<u32>,
+    pub stop_loss_bps: Option<u32>,
+    pub trailing_stop_bps: Option<u32>,
+    pub market_cap_sol: Option<String>,
+    pub peak_value: Option<String>,
+    pub status: Option<String>,
+}
+
+impl From<&ExitRule> for ExitRule4Node {
+    fn from(rule: &ExitRule) -> Self {
+        ExitRule4Node {
+            id: Some(rule.id),
+            wallet: rule.wallet.clone(),
+            mint: rule.mint.clone(),
+            entry_sol: rule.entry_sol.to_string(),
+            token_amount: rule.token_amount.to_string(),
+            take_profit_bps: rule.take_profit_bps.map(|bps| bps as u32),
+            stop_loss_bps: rule.stop_loss_bps.map(|bps| bps as u32),
+            trailing_stop_bps: rule.trailing_stop_bps.map(|bps| bps as u32),
+            market_cap_sol: rule.market_cap_sol.map(|cap| cap.to_string()),
+            peak_value: Some(rule.peak_value.to_string()),
+            status: Some(rule.status.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct ExitTriggerEvent {
+    pub rule_id: u32,
+    pub mint: String,
+    pub reason: String, // "take_profit" | "stop_loss" | "trailing_stop" | "market_cap" | "migrated"
+    pub value: String,
+    pub entry_sol: String,
+    pub responses: Vec<String>,
+    pub error: Option<String>,
+}
+
+pub type ExitTriggerCallback = Arc<dyn Fn(ExitTriggerEvent) + Send + Sync>;
+
+impl ExitRule {
+
+    pub fn is_active(&self) -> bool {
+        self.status == RULE_STATUS_ACTIVE
+    }
+
+    // which rule fires for a position currently worth `value` lamports, if any.
+    // `peak_value` is raised as a side effect
+    pub fn evaluate(&mut self, value: u64, market_cap: u64) -> Option<&'static str> {
+        self.peak_value = self.peak_value.max(value);
+        let value = value as u128;
+        let entry = self.entry_sol as u128;
+
+        if let Some(cap) = self.market_cap_sol {
+            if market_cap >= cap {
+                return Some("market_cap");
+            }
+        }
+        if let Some(bps) = self.take_profit_bps {
+            if value * 10_000 >= entry * bps as u128 {
+                return Some("take_profit");
+            }
+        }
+        if let Some(bps) = self.stop_loss_bps {
+            if value * 10_000 <= entry * bps as u128 {
+                return Some("stop_loss");
+            }
+        }
+        if let Some(bps) = self.trailing_stop_bps {
+            let floor = self.peak_value as u128 * 10_000u128.saturating_sub(bps as u128);
+            if value * 10_000 <= floor {
+                return Some("trailing_stop");
+            }
+        }
+        None
+    }
+}
+
+// market cap in lamports implied by the curve price
+pub fn get_market_cap(curve: &BondingCurveInfo) -> u64 {
+    if curve.virtual_token_reserves == 0 {
+        return 0;
+    }
+    (curve.virtual_sol_reserves as u128 * curve.token_total_supply as u128
+        / curve.virtual_token_reserves as u128) as u64
+}
+
+struct RuleBook {
+    rules: Vec<ExitRule>,
+    next_id: u32,
+}
+
+static RULE_BOOK: OnceLock<Mutex<RuleBook>> = OnceLock::new();
+static RULES_VERSION: AtomicU64 = AtomicU64::new(0);
+
+fn exit_rules_path() -> String {
+    dotenv().ok();
+    env::var("EXIT_RULES_PATH_RUST").unwrap_or_else(|_| DEFAULT_EXIT_RULES_PATH.to_string())
+}
+
+fn load_rules(path: &str) -> Vec<ExitRule> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| match serde_json::from_str(&content) {
+            Ok(rules) => Some(rules),
+            Err(e) => {
+                warn!("exit rules file unreadable, starting empty: {}", e);
+                None
+            }
+        })
+        .unwrap_or_default()
+}
+
+fn write_rules(path: &str, rules: &[ExitRule]) -> Result<()> {
+    let tmp_path = format!("{}.tmp", path);
+    fs::write(&tmp_path, serde_json::to_string_pretty(rules)?)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+fn rule_book() -> &'static Mutex<RuleBook> {
+    RULE_BOOK.get_or_init(|| {
+        let rules = load_rules(&exit_rules_path());
+        let next_id = rules.iter().map(|rule| rule.id).max().unwrap_or(0) + 1;
+        Mutex::new(RuleBook { rules, next_id })
+    })
+}
+
+fn save_rules(rules: &[ExitRule]) -> Result<()> {
+    write_rules(&exit_rules_path(), rules)
+}
+
+pub fn add_exit_rule(mut rule: ExitRule) -> Result<u32> {
+    Pubkey::from_str(&rule.wallet)?;
+    Pubkey::from_str(&rule.mint)?;
+    if rule.entry_sol == 0 || rule.token_amount == 0 {
+        bail!("entry_sol and token_amount must be set");
+    }
+    if rule.take_profit_bps.is_none() && rule.stop_loss_bps.is_none() &&
+        rule.trailing_stop_bps.is_none() && rule.market_cap_sol.is_none() {
+        bail!("exit rule needs at least one condition");
+    }
+
+    let mut book = rule_book().lock().unwrap();
+    rule.id = book.next_id;
+    rule.peak_value = rule.peak_value.max(rule.entry_sol);
+    rule.status = RULE_STATUS_ACTIVE.to_string();
+    book.next_id += 1;
+    book.rules.push(rule.clone());
+    save_rules(&book.rules)?;
+    RULES_VERSION.fetch_add(1, Ordering::Relaxed);
+    Ok(rule.id)
+}
+
+pub fn remove_exit_rule(rule_id: u32) -> Result<bool> {
+    let mut book = rule_book().lock().unwrap();
+    let before = book.rules.len();
+    book.rules.retain(|rule| rule.id != rule_id);
+    if book.rules.len() == before {
+        return Ok(false);
+    }
+    save_rules(&book.rules)?;
+    RULES_VERSION.fetch_add(1, Ordering::Relaxed);
+    Ok(true)
+}
+
+pub fn list_exit_rules() -> Vec<ExitRule> {
+    rule_book().lock().unwrap().rules.clone()
+}
+
+fn active_mints(wallet: &str) -> Vec<Pubkey> {
+    let mut mints: Vec<Pubkey> = rule_book().lock().unwrap().rules.iter()
+        .filter(|rule| rule.is_active() && rule.wallet == wallet)
+        .filter_map(|rule| Pubkey::from_str(&rule.mint).ok())
+        .collect();
+    mints.sort();
+    mints.dedup();
+    mints
+}
+
+// evaluate the wallet's active rules on `mint`, fired rules are switched off before selling
+fn fire_rules(wallet: &str, mint: &Pubkey, curve: &BondingCurveInfo, fee_bps: u64) -> Vec<(ExitRule, &'static str, u64)> {
+    let mut book = rule_book().lock().unwrap();
+    let mint = mint.to_string();
+    let market_cap = get_market_cap(curve);
+    let mut fired = vec![];
+    let mut dirty = false;
+
+    for rule in book.rules.iter_mut().filter(|rule| rule.is_active() && rule.wallet == wallet && rule.mint == mint) {
+        let peak = rule.peak_value;
+        let value = curve.get_sell_sol_amount(rule.token_amount, fee_bps);
+        if let Some(reason) = rule.evaluate(value, market_cap) {
+            rule.status = RULE_STATUS_TRIGGERED.to_string();
+            fired.push((rule.clone(), reason, value));
+        }
+        dirty |= rule.peak_value != peak || !rule.is_active();
+    }
+
+    if dirty {
+        if let Err(e) = save_rules(&book.rules) {
+            warn!("exit rules not saved: {}", e);
+        }
+    }
+    fired
+}
+
+// the curve of `mint` completed: its active rules can't be valued on it any more, switch them
+// off and drop the mint from the watcher
+fn retire_rules(wallet: &str, mint: &Pubkey) -> Vec<ExitRule> {
+    let mut book = rule_book().lock().unwrap();
+    let mint = mint.to_string();
+    let mut retired = vec![];
+    for rule in book.rules.iter_mut().filter(|rule| rule.is_active() && rule.wallet == wallet && rule.mint == mint) {
+        rule.status = RULE_STATUS_MIGRATED.to_string();
+        retired.push(rule.clone());
+    }
+    if !retired.is_empty() {
+        if let Err(e) = save_rules(&book.rules) {
+            warn!("exit rules not saved: {}", e);
+        }
+        RULES_VERSION.fetch_add(1, Ordering::Relaxed);
+    }
+    retired
+}
+
+fn migrated_event(rule: &ExitRule) -> ExitTriggerEvent {
+    ExitTriggerEvent {
+        rule_id: rule.id,
+        mint: rule.mint.clone(),
+        reason: "migrated".to_string(),
+        value: "0".to_string(),
+        entry_sol: rule.entry_sol.to_string(),
+        responses: vec![],
+        error: Some("bonding curve completed, the position trades on PumpSwap and the rule was switched off".to_string()),
+    }
+}
+
+// the rule sells its own position size, capped by what the wallet still holds.
+// `value` is quoted for `rule.token_amount`, the min out is scaled to the amount sold
+pub fn exit_sell_amounts(rule: &ExitRule, balance: u64, value: u64, slippage_bps: u32) -> (u64, u64) {
+    let amount = rule.token_amount.min(balance);
+    let quote = value as u128 * amount as u128 / rule.token_amount.max(1) as u128;
+    let min_out = quote * 10_000u128.saturating_sub(slippage_bps as u128) / 10_000;
+    (amount, min_out as u64)
+}
+
+fn set_rule_status(rule_id: u32, status: &str) {
+    let mut book = rule_book().lock().unwrap();
+    if let Some(rule) = book.rules.iter_mut().find(|rule| rule.id == rule_id) {
+        rule.status = status.to_string();
+    }
+    if let Err(e) = save_rules(&book.rules) {
+        warn!("exit rules not saved: {}", e);
+    }
+}
+
+async fn execute_exit(
+    template: &SwapParam,
+    rule: &ExitRule,
+    reason: &str,
+    value: u64,
+) -> ExitTriggerEvent {
+    let mut event = ExitTriggerEvent {
+        rule_id: rule.id,
+        mint: rule.mint.clone(),
+        reason: reason.to_string(),
+        value: value.to_string(),
+        entry_sol: rule.entry_sol.to_string(),
+        responses: vec![],
+        error: None,
+    };
+
+    let fee_payer = match template.signer() {
+        Ok(fee_payer) => fee_payer,
+        Err(e) => {
+            set_rule_status(rule.id, RULE_STATUS_FAILED);
+            event.error = Some(e.to_string());
+            return event;
+        }
+    };
+
+    let balance = match exit_balance(template, &fee_payer.pubkey(), &rule.mint) {
+        Ok(balance) => balance,
+        Err(e) => {
+            set_rule_status(rule.id, RULE_STATUS_FAILED);
+            event.error = Some(e.to_string());
+            return event;
+        }
+    };
+    let (amount_in, amount_out) = exit_sell_amounts(rule, balance, value, template.slippage_amount);
+    if amount_in == 0 {
+        set_rule_status(rule.id, RULE_STATUS_FAILED);
+        event.error = Some("nothing to sell, the wallet holds none of the position".to_string());
+        return event;
+    }
+
+    let mut param = template.clone();
+    param.token_in = rule.mint.clone();
+    param.token_out = NATIVE_MINT.to_string();
+    param.sell_mode = None;
+    param.amount_in = amount_in;
+    param.amount_out = amount_out;
+
+    let builder = TransactionBuilder::new(template.connection.clone(), fee_payer);
+    match builder.trade(&param).await {
+        Ok(responses) => event.responses = responses,
+        Err(e) => {
+            set_rule_status(rule.id, RULE_STATUS_FAILED);
+            event.error = Some(e.to_string());
+        }
+    }
+    event
+}
+
+// raw token balance of the account the template trades `mint` from
+fn exit_balance(template: &SwapParam, wallet: &Pubkey, mint: &str) -> Result<u64> {
+    let mint = Pubkey::from_str(mint)?;
+    let token_account = match &template.sol_account {
+        Some(sol_account) => resolve_seed_account(wallet, sol_account)?,
+        None => get_associated_token_address(wallet, &mint),
+    };
+    let rpc_client = RpcClient::new(template.connection.clone());
+    let balance = timed_rpc(|| rpc_client.get_token_account_balance(&token_account).map_err(anyhow::Error::from))?;
+    Ok(balance.amount.parse()?)
+}
+
+// watch the curves of every active rule of the template's wallet and sell when one fires.
+// stopped with `stop_listener`
+pub fn start_exit_watcher(
+    template: SwapParam,
+    ws_url: String,
+    callback: ExitTriggerCallback,
+) -> u32 {
+    spawn_listener(move |_| {
+        let template = template.clone();
+        let ws_url = ws_url.clone();
+        let callback = callback.clone();
+        async move { run_exit_watcher(&template, &ws_url, &callback).await }
+    })
+}
+
+// a completed curve retires the mint's rules, otherwise the rules are evaluated on it
+async fn handle_curve(
+    template: &SwapParam,
+    wallet: &str,
+    mint: &Pubkey,
+    curve: &BondingCurveInfo,
+    fee_bps: u64,
+    callback: &ExitTriggerCallback,
+) {
+    if curve.complete {
+        for rule in retire_rules(wallet, mint) {
+            callback(migrated_event(&rule));
+        }
+        return;
+    }
+
+    for (rule, reason, value) in fire_rules(wallet, mint, curve, fee_bps) {
+        callback(execute_exit(template, &rule, reason, value).await);
+    }
+}
+
+async fn run_exit_watcher(
+    template: &SwapParam,
+    ws_url: &str,
+    callback: &ExitTriggerCallback,
+) -> Result<()> {
+    let wallet = template.signer()?.pubkey().to_string();
+    let rpc_client = RpcClient::new(template.connection.clone());
+    let (_, fee_bps) = get_fee_params(&rpc_client);
+
+    let version = RULES_VERSION.load(Ordering::Relaxed);
+    let mints = active_mints(&wallet);
+
+    let client = PubsubClient::new(ws_url).await?;
+    let mut subscriptions = vec![];
+    for mint in &mints {
+        let (stream, _unsubscribe) = client.account_subscribe(
+            &get_bonding_curve_pda(mint)?,
+            Some(RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(CommitmentConfig::processed()),
+                ..RpcAccountInfoConfig::default()
+            }),
+        ).await?;
+        let mint = *mint;
+        subscriptions.push(stream.map(move |response| (mint, response)).boxed());
+    }
+    let mut updates = select_all(subscriptions);
+
+    //a rule may already hold when it is added, or the curve moved while we were resubscribing.
+    //read once after subscribing so nothing between the read and the first update is missed
+    for mint in &mints {
+        match get_bonding_curve(&rpc_client, mint) {
+            Ok(Some(curve)) => handle_curve(template, &wallet, mint, &curve, fee_bps, callback).await,
+            Ok(None) => {},
+            Err(e) => warn!("bonding curve of {} not read: {}", mint, e),
+        }
+    }
+
+    let mut poll = tokio::time::interval(RULES_POLL_INTERVAL);
+    loop {
+        tokio::select! {
+            update = updates.next(), if !mints.is_empty() => {
+                let Some((mint, response)) = update else {
+                    return Err(anyhow!("account subscription closed"));
+                };
+                let Some(curve) = response.value.decode::<Account>()
+                    .and_then(|account| decode_bonding_curve(&account.data)) else { continue };
+                handle_curve(template, &wallet, &mint, &curve, fee_bps, callback).await;
+            },
+            _ = poll.tick() => {
+                //rules changed, resubscribe to the new set of curves
+                if RULES_VERSION.load(Ordering::Relaxed) != version {
+                    return Ok(());
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(token_amount: u64) -> ExitRule {
+        ExitRule {
+            id: 1,
+            wallet: String::new(),
+            mint: String::new(),
+            entry_sol: 1_000_000_000,
+            token_amount,
+            take_profit_bps: Some(20_000),
+            stop_loss_bps: None,
+            trailing_stop_bps: None,
+            market_cap_sol: None,
+            peak_value: 0,
+            status: RULE_STATUS_ACTIVE.to_string(),
+        }
+    }
+
+    #[test]
+    fn sells_the_rule_position_not_the_whole_balance() {
+        // value 2 sol for 1_000 tokens, 5% slippage
+        assert_eq!(exit_sell_amounts(&rule(1_000), 5_000, 2_000_000_000, 500), (1_000, 1_900_000_000));
+    }
+
+    #[test]
+    fn caps_at_the_balance_and_scales_min_out() {
+        assert_eq!(exit_sell_amounts(&rule(1_000), 250, 2_000_000_000, 500), (250, 475_000_000));
+        assert_eq!(exit_sell_amounts(&rule(1_000), 0, 2_000_000_000, 500), (0, 0));
+    }
+
+    fn conditions(
+        take_profit_bps: Option<u64>,
+        stop_loss_bps: Option<u64>,
+        trailing_stop_bps: Option<u64>,
+        market_cap_sol: Option<u64>,
+    ) -> ExitRule {
+        ExitRule {
+            take_profit_bps,
+            stop_loss_bps,
+            trailing_stop_bps,
+            market_cap_sol,
+            peak_value: 1_000_000_000,
+            ..rule(1_000)
+        }
+    }
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("exit_rules_{}_{}.json", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn take_profit_fires_at_the_target_multiple() {
+        let mut rule = conditions(Some(20_000), None, None, None);
+        assert_eq!(rule.evaluate(1_999_999_999, 0), None);
+        assert_eq!(rule.evaluate(2_000_000_000, 0), Some("take_profit"));
+    }
+
+    #[test]
+    fn stop_loss_fires_below_the_floor() {
+        let mut rule = conditions(None, Some(5_000), None, None);
+        assert_eq!(rule.evaluate(500_000_001, 0), None);
+        assert_eq!(rule.evaluate(500_000_000, 0), Some("stop_loss"));
+    }
+
+    #[test]
+    fn trailing_stop_follows_the_high_water_mark() {
+        let mut rule = conditions(None, None, Some(2_000), None);
+        assert_eq!(rule.evaluate(1_500_000_000, 0), None);
+        assert_eq!(rule.peak_value, 1_500_000_000);
+        // 20% off the 1.5 sol peak is 1.2 sol, still above the entry
+        assert_eq!(rule.evaluate(1_250_000_000, 0), None);
+        assert_eq!(rule.peak_value, 1_500_000_000);
+        assert_eq!(rule.evaluate(1_200_000_000, 0), Some("trailing_stop"));
+    }
+
+    #[test]
+    fn market_cap_is_checked_first() {
+        let mut rule = conditions(Some(20_000), None, None, Some(50_000_000_000));
+        assert_eq!(rule.evaluate(1_000_000_000, 49_999_999_999), None);
+        assert_eq!(rule.evaluate(3_000_000_000, 50_000_000_000), Some("market_cap"));
+    }
+
+    #[test]
+    fn rules_survive_a_reload() {
+        let path = temp_path("reload");
+        let mut triggered = conditions(None, None, Some(2_000), None);
+        triggered.id = 7;
+        triggered.peak_value = 1_500_000_000;
+        triggered.status = RULE_STATUS_TRIGGERED.to_string();
+        let rules = vec![conditions(Some(20_000), Some(5_000), None, Some(50_000_000_000)), triggered];
+
+        write_rules(&path, &rules).unwrap();
+        let loaded = load_rules(&path);
+        fs::remove_file(&path).ok();
+
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&rules).unwrap());
+    }
+
+    #[test]
+    fn missing_or_unreadable_files_start_empty() {
+        let path = temp_path("unreadable");
+        assert!(load_rules(&path).is_empty());
+        fs::write(&path, "not json").unwrap();
+        assert!(load_rules(&path).is_empty());
+        fs::remove_file(&path).ok();
+    }
+}
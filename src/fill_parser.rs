@@ -0,0 +1,451 @@
+use anyhow::{anyhow, Result};
+use napi_derive::napi;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use spl_token::native_mint::ID as WSOL_MINT;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta,
+    UiTransactionEncoding,
+    UiTransactionTokenBalance,
+};
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::configs::bribe::{get_sender_name, get_tip_account_sender};
+use crate::events::{decode_trade_events, TradeEvent};
+
+// what a landed transaction actually did to one wallet. deltas are signed, positive = received
+#[derive(Debug, Clone)]
+pub struct RealizedFill {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub error: Option<String>,
+    pub wallet: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub is_buy: Option<bool>,
+    pub token_delta: i128,
+    pub sol_delta: i128,      // wallet lamports, everything included
+    pub swap_sol_delta: i128, // lamports that went into or came out of the swap itself
+    pub fee: u64,
+    pub tip: u64,
+    pub relay: Option<u8>,
+    pub rent: i128,           // token account rent paid, negative when reclaimed
+    pub compute_units: Option<u64>,
+    pub trade_events: Vec<TradeEvent>,
+}
+
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct RealizedFill4Node {
+    pub signature: String,
+    pub slot: i64,
+    pub block_time: Option<i64>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub wallet: String,
+    pub mint: Option<String>,
+    pub is_buy: Option<bool>,
+    pub token_delta: String,
+    pub sol_delta: String,
+    pub swap_sol_delta: String,
+    pub fee: String,
+    pub tip: String,
+    pub relay: Option<String>,
+    pub rent: String,
+    pub compute_units: Option<String>,
+}
+
+impl From<&RealizedFill> for RealizedFill4Node {
+    fn from(fill: &RealizedFill) -> Self {
+        RealizedFill4Node {
+            signature: fill.signature.clone(),
+            slot: fill.slot as i64,
+            block_time: fill.block_time,
+            success: fill.error.is_none(),
+            error: fill.error.clone(),
+            wallet: fill.wallet.to_string(),
+            mint: fill.mint.map(|mint| mint.to_string()),
+            is_buy: fill.is_buy,
+            token_delta: fill.token_delta.to_string(),
+            sol_delta: fill.sol_delta.to_string(),
+            swap_sol_delta: fill.swap_sol_delta.to_string(),
+            fee: fill.fee.to_string(),
+            tip: fill.tip.to_string(),
+            relay: fill.relay.map(|relay| get_sender_name(relay).to_string()),
+            rent: fill.rent.to_string(),
+            compute_units: fill.compute_units.map(|units| units.to_string()),
+        }
+    }
+}
+
+pub fn fetch_transaction(
+    rpc_client: &RpcClient,
+    signature: &str,
+) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+    rpc_client
+        .get_transaction_with_config(
+            &Signature::from_str(signature)?,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .map_err(|e| anyhow!("transaction {} not available: {}", signature, e))
+}
+
+// `wallet` defaults to the fee payer
+pub fn fetch_fill(
+    rpc_client: &RpcClient,
+    signature: &str,
+    wallet: Option<&Pubkey>,
+) -> Result<RealizedFill> {
+    let tx = fetch_transaction(rpc_client, signature)?;
+    parse_fill(&tx, signature, wallet)
+}
+
+fn token_balances(balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>) -> Vec<UiTransactionTokenBalance> {
+    Option::from(balances.clone()).unwrap_or_default()
+}
+
+fn is_owned_by(balance: &UiTransactionTokenBalance, wallet: &str) -> bool {
+    matches!(&balance.owner, OptionSerializer::Some(owner) if owner == wallet)
+}
+
+pub fn parse_fill(
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+    signature: &str,
+    wallet: Option<&Pubkey>,
+) -> Result<RealizedFill> {
+    let meta = tx.transaction.meta.as_ref()
+        .ok_or_else(|| anyhow!("transaction {} has no status meta", signature))?;
+    let versioned = tx.transaction.transaction.decode()
+        .ok_or_else(|| anyhow!("transaction {} could not be decoded", signature))?;
+
+    //static keys first, then the lookup table addresses in writable, readonly order
+    let mut account_keys: Vec<Pubkey> = versioned.message.static_account_keys().to_vec();
+    if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
+        for key in loaded.writable.iter().chain(loaded.readonly.iter()) {
+            account_keys.push(Pubkey::from_str(key)?);
+        }
+    }
+
+    let wallet = wallet.copied().unwrap_or(account_keys[0]);
+    let wallet_str = wallet.to_string();
+    let wallet_index = account_keys.iter().position(|key| *key == wallet)
+        .ok_or_else(|| anyhow!("{} is not part of transaction {}", wallet, signature))?;
+
+    let lamport_delta = |index: usize| -> i128 {
+        let pre = meta.pre_balances.get(index).copied().unwrap_or(0) as i128;
+        let post = meta.post_balances.get(index).copied().unwrap_or(0) as i128;
+        post - pre
+    };
+
+    let sol_delta = lamport_delta(wallet_index);
+    let fee = if wallet_index == 0 { meta.fee } else { 0 };
+
+    //tips, the tip account tells which relay carried the transaction. a failed transaction
+    //rolls the tip transfer back, so the relay comes from the account being there at all
+    let mut tip: u64 = 0;
+    let mut relay = None;
+    for (index, key) in account_keys.iter().enumerate() {
+        if let Some(sender) = get_tip_account_sender(&key.to_string()) {
+            relay = Some(sender);
+            let delta = lamport_delta(index);
+            if delta > 0 {
+                tip += delta as u64;
+            }
+        }
+    }
+
+    //token deltas of the wallet per mint, wsol is only a vehicle for sol and skipped
+    let pre_tokens = token_balances(&meta.pre_token_balances);
+    let post_tokens = token_balances(&meta.post_token_balances);
+    let mut token_deltas: HashMap<String, i128> = HashMap::new();
+    let mut rent: i128 = 0;
+    let wsol_mint = WSOL_MINT.to_string();
+
+    for balance in pre_tokens.iter().filter(|b| is_owned_by(b, &wallet_str) && b.mint != wsol_mint) {
+        let amount: i128 = balance.ui_token_amount.amount.parse().unwrap_or(0);
+        *token_deltas.entry(balance.mint.clone()).or_insert(0) -= amount;
+
+        let index = balance.account_index as usize;
+        let post_lamports = meta.post_balances.get(index).copied().unwrap_or(0);
+        if post_lamports == 0 {
+            rent -= meta.pre_balances.get(index).copied().unwrap_or(0) as i128;
+        }
+    }
+    for balance in post_tokens.iter().filter(|b| is_owned_by(b, &wallet_str) && b.mint != wsol_mint) {
+        let amount: i128 = balance.ui_token_amount.amount.parse().unwrap_or(0);
+        *token_deltas.entry(balance.mint.clone()).or_insert(0) += amount;
+
+        let index = balance.account_index as usize;
+        let pre_lamports = meta.pre_balances.get(index).copied().unwrap_or(0);
+        if pre_lamports == 0 {
+            rent += meta.post_balances.get(index).copied().unwrap_or(0) as i128;
+        }
+    }
+
+    let logs: Vec<String> = Option::from(meta.log_messages.clone()).unwrap_or_default();
+    let trade_events: Vec<TradeEvent> = decode_trade_events(&logs)
+        .into_iter()
+        .filter(|event| event.user == wallet)
+        .collect();
+
+    //prefer the mint pump.fun reported, otherwise the biggest token move
+    let mint = match trade_events.first() {
+        Some(event) => Some(event.mint),
+        None => token_deltas.iter()
+            .filter(|(_, delta)| **delta != 0)
+            .max_by_key(|(_, delta)| delta.abs())
+            .and_then(|(mint, _)| Pubkey::from_str(mint).ok()),
+    };
+    let token_delta = mint
+        .and_then(|mint| token_deltas.get(&mint.to_string()).copied())
+        .unwrap_or(0);
+    let is_buy = match trade_events.first() {
+        Some(event) => Some(event.is_buy),
+        None if token_delta != 0 => Some(token_delta > 0),
+        None => None,
+    };
+
+    Ok(RealizedFill {
+        signature: signature.to_string(),
+        slot: tx.slot,
+        block_time: tx.block_time,
+        error: meta.err.as_ref().map(|e| e.to_string()),
+        wallet,
+        mint,
+        is_buy,
+        token_delta,
+        sol_delta,
+        swap_sol_delta: sol_delta + fee as i128 + tip as i128 + rent,
+        fee,
+        tip,
+        relay,
+        rent,
+        compute_units: Option::from(meta.compute_units_consumed.clone()),
+        trade_events,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{Engine as _, engine::general_purpose};
+    use borsh::to_vec;
+    use serde_json::{json, Value};
+    use solana_sdk::hash::Hash;
+    use solana_sdk::message::Message;
+    use solana_sdk::transaction::Transaction;
+    use crate::configs::bribe::SENDER_JITO;
+    use crate::configs::global::{PUMPFUN_PROGRAM_ID, TOKEN_PROGRAM_ID};
+    use crate::utils::sighash;
+
+    const JITO_TIP_ACCOUNT: &str = "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5";
+    const ATA_RENT: u64 = 2_039_280;
+    const TOKENS: u64 = 34_281_150_129_545;
+
+    struct Accounts {
+        wallet: Pubkey,
+        bonding_curve: Pubkey,
+        mint: Pubkey,
+        keys: Vec<Pubkey>,
+    }
+
+    // payer, its token account, the curve, a jito tip account, then readonly mint and program
+    fn accounts() -> Accounts {
+        let (wallet, bonding_curve, mint) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let keys = vec![
+            wallet,
+            Pubkey::new_unique(),
+            bonding_curve,
+            Pubkey::from_str(JITO_TIP_ACCOUNT).unwrap(),
+            mint,
+            Pubkey::from_str(PUMPFUN_PROGRAM_ID).unwrap(),
+        ];
+        Accounts { wallet, bonding_curve, mint, keys }
+    }
+
+    fn trade_log(accounts: &Accounts, is_buy: bool, sol_amount: u64) -> String {
+        let event = TradeEvent {
+            mint: accounts.mint,
+            sol_amount,
+            token_amount: TOKENS,
+            is_buy,
+            user: accounts.wallet,
+            timestamp: 1_750_000_000,
+            virtual_sol_reserves: 30_990_099_009,
+            virtual_token_reserves: 1_073_000_000_000_000 - TOKENS,
+        };
+        let mut data = sighash("event", "TradeEvent").to_vec();
+        data.extend(to_vec(&event).unwrap());
+        format!("Program data: {}", general_purpose::STANDARD.encode(data))
+    }
+
+    fn token_balance(accounts: &Accounts, amount: u64) -> Value {
+        mint_balance(accounts, 1, &accounts.mint, amount)
+    }
+
+    fn mint_balance(accounts: &Accounts, index: u8, mint: &Pubkey, amount: u64) -> Value {
+        json!({
+            "accountIndex": index,
+            "mint": mint.to_string(),
+            "owner": accounts.wallet.to_string(),
+            "programId": TOKEN_PROGRAM_ID,
+            "uiTokenAmount": {
+                "amount": amount.to_string(),
+                "decimals": 6,
+                "uiAmount": amount as f64 / 1e6,
+                "uiAmountString": (amount as f64 / 1e6).to_string(),
+            },
+        })
+    }
+
+    // a getTransaction response with base64 encoding, as the rpc returns it
+    fn fixture(
+        accounts: &Accounts,
+        err: Value,
+        pre_balances: [u64; 6],
+        post_balances: [u64; 6],
+        pre_tokens: Vec<Value>,
+        post_tokens: Vec<Value>,
+        logs: Vec<String>,
+    ) -> EncodedConfirmedTransactionWithStatusMeta {
+        let message = Message::new_with_compiled_instructions(1, 0, 2, accounts.keys.clone(), Hash::default(), vec![]);
+        let transaction = Transaction { signatures: vec![Signature::default()], message };
+        let status = if err.is_null() { json!({ "Ok": null }) } else { json!({ "Err": err }) };
+
+        serde_json::from_value(json!({
+            "slot": 350_000_000u64,
+            "blockTime": 1_750_000_000i64,
+            "version": "legacy",
+            "transaction": [general_purpose::STANDARD.encode(bincode::serialize(&transaction).unwrap()), "base64"],
+            "meta": {
+                "err": err,
+                "status": status,
+                "fee": 5_000,
+                "preBalances": pre_balances,
+                "postBalances": post_balances,
+                "innerInstructions": [],
+                "logMessages": logs,
+                "preTokenBalances": pre_tokens,
+                "postTokenBalances": post_tokens,
+                "rewards": [],
+                "loadedAddresses": { "writable": [], "readonly": [] },
+                "computeUnitsConsumed": 61_000,
+            },
+        })).unwrap()
+    }
+
+    #[test]
+    fn buy_through_jito_attributes_the_tip() {
+        let accounts = accounts();
+        let sol_in = 1_010_000_000;
+        let tx = fixture(
+            &accounts,
+            Value::Null,
+            [10_000_000_000, 0, 1_000_000_000, 500_000_000, 1_461_600, 1],
+            [10_000_000_000 - 5_000 - 1_000_000 - ATA_RENT - sol_in, ATA_RENT, 1_000_000_000 + sol_in, 501_000_000, 1_461_600, 1],
+            vec![],
+            vec![token_balance(&accounts, TOKENS)],
+            vec![trade_log(&accounts, true, sol_in)],
+        );
+
+        let fill = parse_fill(&tx, "buy", None).unwrap();
+        assert_eq!(fill.wallet, accounts.wallet);
+        assert_eq!((fill.slot, fill.block_time), (350_000_000, Some(1_750_000_000)));
+        assert!(fill.error.is_none());
+        assert_eq!(fill.mint, Some(accounts.mint));
+        assert_eq!(fill.is_buy, Some(true));
+        assert_eq!(fill.token_delta, TOKENS as i128);
+        assert_eq!(fill.fee, 5_000);
+        assert_eq!(fill.tip, 1_000_000);
+        assert_eq!(fill.relay, Some(SENDER_JITO));
+        assert_eq!(fill.rent, ATA_RENT as i128);
+        assert_eq!(fill.sol_delta, -((5_000 + 1_000_000 + ATA_RENT + sol_in) as i128));
+        assert_eq!(fill.swap_sol_delta, -(sol_in as i128));
+        assert_eq!(fill.compute_units, Some(61_000));
+        assert_eq!(fill.trade_events.len(), 1);
+
+        let node = RealizedFill4Node::from(&fill);
+        assert_eq!(node.relay.as_deref(), Some("jito"));
+        assert!(node.success);
+    }
+
+    #[test]
+    fn sell_that_closes_the_account_reclaims_rent() {
+        let mut accounts = accounts();
+        // sent over plain rpc, no tip account
+        accounts.keys[3] = Pubkey::new_unique();
+        let sol_out = 980_198_017;
+        // no trade event, side and mint come from the token balances. the wallet's wsol
+        // account moves more than the token but is only a vehicle for sol
+        let tx = fixture(
+            &accounts,
+            Value::Null,
+            [1_000_000_000, ATA_RENT, 2_000_000_000, 500_000_000, 1_461_600, 1],
+            [1_000_000_000 - 5_000 + ATA_RENT + sol_out, 0, 2_000_000_000 - sol_out, 500_000_000, 1_461_600, 1],
+            vec![token_balance(&accounts, TOKENS), mint_balance(&accounts, 2, &WSOL_MINT, 0)],
+            vec![mint_balance(&accounts, 2, &WSOL_MINT, TOKENS * 10)],
+            vec![],
+        );
+
+        let fill = parse_fill(&tx, "sell", None).unwrap();
+        assert_eq!(fill.mint, Some(accounts.mint));
+        assert_eq!(fill.is_buy, Some(false));
+        assert_eq!(fill.token_delta, -(TOKENS as i128));
+        assert_eq!((fill.tip, fill.relay), (0, None));
+        assert_eq!(fill.rent, -(ATA_RENT as i128));
+        assert_eq!(fill.swap_sol_delta, sol_out as i128);
+        assert!(fill.trade_events.is_empty());
+    }
+
+    #[test]
+    fn failed_transactions_keep_the_error_and_fee() {
+        let accounts = accounts();
+        let tx = fixture(
+            &accounts,
+            json!({ "InstructionError": [0, { "Custom": 6002 }] }),
+            [1_000_000_000, 0, 1_000_000_000, 500_000_000, 1_461_600, 1],
+            [1_000_000_000 - 5_000, 0, 1_000_000_000, 500_000_000, 1_461_600, 1],
+            vec![],
+            vec![],
+            vec![],
+        );
+
+        let fill = parse_fill(&tx, "failed", None).unwrap();
+        assert!(fill.error.is_some());
+        assert_eq!((fill.mint, fill.is_buy, fill.token_delta), (None, None, 0));
+        assert_eq!(fill.fee, 5_000);
+        // the tip transfer was rolled back, the tip account still names the relay
+        assert_eq!((fill.tip, fill.relay), (0, Some(SENDER_JITO)));
+        assert_eq!(fill.swap_sol_delta, 0);
+        assert!(!RealizedFill4Node::from(&fill).success);
+    }
+
+    #[test]
+    fn other_wallets_pay_no_fee_and_must_be_in_the_transaction() {
+        let accounts = accounts();
+        let tx = fixture(
+            &accounts,
+            Value::Null,
+            [1_000_000_000; 6],
+            [1_000_000_000; 6],
+            vec![],
+            vec![],
+            vec![],
+        );
+
+        let fill = parse_fill(&tx, "other", Some(&accounts.bonding_curve)).unwrap();
+        assert_eq!(fill.wallet, accounts.bonding_curve);
+        assert_eq!(fill.fee, 0);
+        assert!(parse_fill(&tx, "other", Some(&Pubkey::new_unique())).is_err());
+    }
+}
@@ -0,0 +1,355 @@
+use anyhow::Result;
+use futures::{SinkExt, StreamExt};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_grpc_proto::prelude::{
+    subscribe_request_filter_accounts_filter::Filter as AccountsFilter,
+    subscribe_request_filter_accounts_filter_memcmp::Data as MemcmpData,
+    subscribe_update::UpdateOneof,
+    CommitmentLevel,
+    SubscribeRequest,
+    SubscribeRequestFilterAccounts,
+    SubscribeRequestFilterAccountsFilter,
+    SubscribeRequestFilterAccountsFilterMemcmp,
+    SubscribeRequestFilterTransactions,
+};
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::bonding_curve_cache::{clear_bonding_curves, decode_bonding_curve, evict_idle_bonding_curves, update_bonding_curve};
+use crate::configs::global::PUMPFUN_PROGRAM_ID;
+use crate::events::decode_pumpfun_events;
+use crate::listener::{spawn_listener, EventCallback};
+use crate::utils::sighash;
+
+// yellowstone geyser source: pump.fun transactions go through the same event callback as the
+// websocket listener, bonding curve account updates land in the bonding curve cache
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const CURVE_IDLE_TTL: Duration = Duration::from_secs(600);
+const EVICT_INTERVAL: Duration = Duration::from_secs(60);
+
+// the cache is only valid while the stream feeding it is up. dropped when `run_geyser`
+// returns and also when `stop_listener` aborts the task, which drops its future
+struct CurveCacheGuard;
+
+impl Drop for CurveCacheGuard {
+    fn drop(&mut self) {
+        clear_bonding_curves();
+    }
+}
+
+pub fn start_geyser_listener(
+    endpoint: String,
+    x_token: Option<String>,
+    callback: EventCallback,
+) -> u32 {
+    spawn_listener(move |_| {
+        let endpoint = endpoint.clone();
+        let x_token = x_token.clone();
+        let callback = callback.clone();
+        async move {
+            let _guard = CurveCacheGuard;
+            run_geyser(&endpoint, x_token, &callback).await
+        }
+    })
+}
+
+fn subscribe_request() -> SubscribeRequest {
+    let mut transactions = HashMap::new();
+    transactions.insert(
+        "pumpfun".to_string(),
+        SubscribeRequestFilterTransactions {
+            vote: Some(false),
+            failed: Some(false),
+            account_include: vec![PUMPFUN_PROGRAM_ID.to_string()],
+            ..Default::default()
+        },
+    );
+
+    // only BondingCurve accounts, matched on their discriminator. the account size is not
+    // filtered on, it grew when pump.fun added fields
+    let mut accounts = HashMap::new();
+    accounts.insert(
+        "bonding_curves".to_string(),
+        SubscribeRequestFilterAccounts {
+            owner: vec![PUMPFUN_PROGRAM_ID.to_string()],
+            filters: vec![SubscribeRequestFilterAccountsFilter {
+                filter: Some(AccountsFilter::Memcmp(SubscribeRequestFilterAccountsFilterMemcmp {
+                    offset: 0,
+                    data: Some(MemcmpData::Bytes(sighash("account", "BondingCurve").to_vec())),
+                })),
+            }],
+            ..Default::default()
+        },
+    );
+
+    SubscribeRequest {
+        transactions,
+        accounts,
+        commitment: Some(CommitmentLevel::Processed as i32),
+        ..Default::default()
+    }
+}
+
+async fn run_geyser(
+    endpoint: &str,
+    x_token: Option<String>,
+    callback: &EventCallback,
+) -> Result<()> {
+    let mut client = GeyserGrpcClient::connect_with_timeout(
+        endpoint.to_string(),
+        x_token,
+        None,
+        Some(CONNECT_TIMEOUT),
+        None,
+        false,
+    ).await?;
+
+    let (mut subscribe_tx, mut stream) = client.subscribe().await?;
+    subscribe_tx.send(subscribe_request()).await?;
+
+    let mut evict = tokio::time::interval(EVICT_INTERVAL);
+    loop {
+        let message = tokio::select! {
+            message = stream.next() => message,
+            _ = evict.tick() => {
+                evict_idle_bonding_curves(CURVE_IDLE_TTL);
+                continue;
+            },
+        };
+        let Some(message) = message else { break };
+
+        match message?.update_oneof {
+            Some(UpdateOneof::Transaction(update)) => {
+                let Some(info) = update.transaction else { continue };
+                let Some(meta) = info.meta else { continue };
+                if meta.err.is_some() {
+                    continue;
+                }
+                let signature = Signature::try_from(info.signature.as_slice())
+                    .map(|signature| signature.to_string())
+                    .unwrap_or_default();
+
+                for event in decode_pumpfun_events(&meta.log_messages) {
+                    callback(&signature, update.slot, &event);
+                }
+            },
+            Some(UpdateOneof::Account(update)) => {
+                let Some(account) = update.account else { continue };
+                let Ok(pubkey) = Pubkey::try_from(account.pubkey.as_slice()) else { continue };
+                if let Some(info) = decode_bonding_curve(&account.data) {
+                    update_bonding_curve(pubkey, update.slot, info);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bonding_curve_cache::get_cached_bonding_curve;
+    use crate::contracts::pumpfun_proxy::BondingCurveInfo;
+    use crate::events::{PumpfunEvent, TradeEvent};
+    use crate::listener::stop_listener;
+    use base64::{Engine as _, engine::general_purpose};
+    use futures::stream::{self, BoxStream};
+    use std::sync::{Arc, Mutex};
+    use yellowstone_grpc_proto::prelude::{
+        geyser_server::{Geyser, GeyserServer},
+        GetBlockHeightRequest, GetBlockHeightResponse, GetLatestBlockhashRequest, GetLatestBlockhashResponse,
+        GetSlotRequest, GetSlotResponse, GetVersionRequest, GetVersionResponse, IsBlockhashValidRequest,
+        IsBlockhashValidResponse, PingRequest, PongResponse, SubscribeUpdate, SubscribeUpdateAccount,
+        SubscribeUpdateAccountInfo, SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo,
+        TransactionStatusMeta,
+    };
+    use yellowstone_grpc_proto::tonic::{self, transport::Server, Request, Response, Status, Streaming};
+
+    // replays canned updates once the subscribe request arrives and keeps the stream open.
+    // like the real server it answers before reading the request, the client sends it after
+    struct MockGeyser {
+        updates: Vec<SubscribeUpdate>,
+        received: Arc<Mutex<Option<SubscribeRequest>>>,
+    }
+
+    #[tonic::async_trait]
+    impl Geyser for MockGeyser {
+        type SubscribeStream = BoxStream<'static, Result<SubscribeUpdate, Status>>;
+
+        async fn subscribe(&self, request: Request<Streaming<SubscribeRequest>>) -> Result<Response<Self::SubscribeStream>, Status> {
+            let mut requests = request.into_inner();
+            let received = self.received.clone();
+            let updates = self.updates.clone();
+            let stream = stream::once(async move {
+                let subscribe = requests.message().await.ok().flatten();
+                *received.lock().unwrap() = subscribe;
+                stream::iter(updates.into_iter().map(Ok))
+            });
+            Ok(Response::new(stream.flatten().chain(stream::pending()).boxed()))
+        }
+
+        async fn ping(&self, _: Request<PingRequest>) -> Result<Response<PongResponse>, Status> {
+            Err(Status::unimplemented("ping"))
+        }
+
+        async fn get_latest_blockhash(&self, _: Request<GetLatestBlockhashRequest>) -> Result<Response<GetLatestBlockhashResponse>, Status> {
+            Err(Status::unimplemented("get_latest_blockhash"))
+        }
+
+        async fn get_block_height(&self, _: Request<GetBlockHeightRequest>) -> Result<Response<GetBlockHeightResponse>, Status> {
+            Err(Status::unimplemented("get_block_height"))
+        }
+
+        async fn get_slot(&self, _: Request<GetSlotRequest>) -> Result<Response<GetSlotResponse>, Status> {
+            Err(Status::unimplemented("get_slot"))
+        }
+
+        async fn is_blockhash_valid(&self, _: Request<IsBlockhashValidRequest>) -> Result<Response<IsBlockhashValidResponse>, Status> {
+            Err(Status::unimplemented("is_blockhash_valid"))
+        }
+
+        async fn get_version(&self, _: Request<GetVersionRequest>) -> Result<Response<GetVersionResponse>, Status> {
+            Err(Status::unimplemented("get_version"))
+        }
+    }
+
+    fn curve_account(pubkey: &Pubkey, slot: u64, complete: bool) -> SubscribeUpdate {
+        let curve = BondingCurveInfo {
+            discriminator: u64::from_le_bytes(sighash("account", "BondingCurve")),
+            virtual_token_reserves: 1_073_000_000_000_000,
+            virtual_sol_reserves: 30_000_000_000,
+            real_token_reserves: 793_100_000_000_000,
+            real_sol_reserves: 0,
+            token_total_supply: 1_000_000_000_000_000,
+            complete,
+            creator: Pubkey::new_unique(),
+        };
+        SubscribeUpdate {
+            filters: vec!["bonding_curves".to_string()],
+            update_oneof: Some(UpdateOneof::Account(SubscribeUpdateAccount {
+                account: Some(SubscribeUpdateAccountInfo {
+                    pubkey: pubkey.to_bytes().to_vec(),
+                    data: borsh::to_vec(&curve).unwrap(),
+                    ..Default::default()
+                }),
+                slot,
+                is_startup: false,
+            })),
+        }
+    }
+
+    fn trade_transaction(event: &TradeEvent, slot: u64) -> SubscribeUpdate {
+        let mut data = sighash("event", "TradeEvent").to_vec();
+        data.extend(borsh::to_vec(event).unwrap());
+        SubscribeUpdate {
+            filters: vec!["pumpfun".to_string()],
+            update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                transaction: Some(SubscribeUpdateTransactionInfo {
+                    signature: vec![7; 64],
+                    meta: Some(TransactionStatusMeta {
+                        log_messages: vec![
+                            format!("Program {} invoke [1]", PUMPFUN_PROGRAM_ID),
+                            format!("Program data: {}", general_purpose::STANDARD.encode(&data)),
+                        ],
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                slot,
+            })),
+        }
+    }
+
+    async fn serve(mock: MockGeyser) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = stream::unfold(listener, |listener| async move {
+            let accepted = listener.accept().await.map(|(socket, _)| socket);
+            Some((accepted, listener))
+        });
+        tokio::spawn(Server::builder().add_service(GeyserServer::new(mock)).serve_with_incoming(incoming));
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn streams_events_and_curves_until_stopped() {
+        let live_curve = Pubkey::new_unique();
+        let completed_curve = Pubkey::new_unique();
+        let trade = TradeEvent {
+            mint: Pubkey::new_unique(),
+            sol_amount: 100_000_000,
+            token_amount: 3_500_000_000_000,
+            is_buy: true,
+            user: Pubkey::new_unique(),
+            timestamp: 1_700_000_000,
+            virtual_sol_reserves: 30_100_000_000,
+            virtual_token_reserves: 1_069_500_000_000_000,
+        };
+
+        let received = Arc::new(Mutex::new(None));
+        let endpoint = serve(MockGeyser {
+            updates: vec![
+                curve_account(&live_curve, 10, false),
+                curve_account(&completed_curve, 10, true),
+                trade_transaction(&trade, 11),
+            ],
+            received: received.clone(),
+        }).await;
+
+        let events = Arc::new(Mutex::new(vec![]));
+        let sink = events.clone();
+        let callback: EventCallback = Arc::new(move |signature: &str, slot: u64, event: &PumpfunEvent| {
+            sink.lock().unwrap().push((signature.to_string(), slot, event.clone()));
+        });
+        let listener_id = start_geyser_listener(endpoint, None, callback);
+
+        for _ in 0..100 {
+            if !events.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        let events = events.lock().unwrap().clone();
+        assert_eq!(events.len(), 1);
+        let (signature, slot, event) = &events[0];
+        assert_eq!(signature, &Signature::from([7; 64]).to_string());
+        assert_eq!(*slot, 11);
+        match event {
+            PumpfunEvent::Trade(event) => {
+                assert_eq!(event.mint, trade.mint);
+                assert_eq!(event.sol_amount, trade.sol_amount);
+                assert!(event.is_buy);
+            },
+            other => panic!("expected a trade event, got {:?}", other),
+        }
+
+        assert!(get_cached_bonding_curve(&live_curve).is_some());
+        assert!(get_cached_bonding_curve(&completed_curve).is_none());
+
+        let request = received.lock().unwrap().clone().expect("subscribe request");
+        let filter = &request.accounts["bonding_curves"].filters[0];
+        match &filter.filter {
+            Some(AccountsFilter::Memcmp(memcmp)) => {
+                assert_eq!(memcmp.offset, 0);
+                assert_eq!(memcmp.data, Some(MemcmpData::Bytes(sighash("account", "BondingCurve").to_vec())));
+            },
+            other => panic!("expected a memcmp filter, got {:?}", other),
+        }
+
+        assert!(stop_listener(listener_id));
+        for _ in 0..100 {
+            if get_cached_bonding_curve(&live_curve).is_none() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(get_cached_bonding_curve(&live_curve).is_none());
+    }
+}
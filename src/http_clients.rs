@@ -0,0 +1,345 @@
+use anyhow::{anyhow, Result};
+use dotenv::dotenv;
+use futures::future::join_all;
+use napi_derive::napi;
+use reqwest::Client;
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use std::collections::HashMap;
+use std::env;
+use std::net::IpAddr;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::configs::bribe::*;
+use crate::keep_alive::{get_relay_key, ping_relay, record_ping};
+use crate::region_probe::resolve_relay_urls;
+use crate::tls::build_tls_config;
+use crate::secret::redact_url;
+
+// one reqwest client per relay so pools, timeouts and the local bind address can be tuned
+// independently. RELAY_LOCAL_ADDRESS_RUST / RELAY_INTERFACE_RUST set the default binding.
+// scheme, auth placement and tls trust are per relay too
+
+#[derive(Debug, Clone)]
+pub struct RelayClientConfig {
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    pub pool_idle_timeout: Duration,
+    pub pool_max_idle_per_host: usize,
+    pub tcp_keepalive: Duration,
+    pub http2_prior_knowledge: bool, // only for endpoints that speak h2c / h2 without alpn
+    pub local_address: Option<IpAddr>,
+    pub interface: Option<String>,
+    pub scheme: String, // "http" | "https", used for endpoints configured without one
+    pub auth_in_header: bool, // send the key as a header on relays that accept one
+    pub ca_cert_paths: Vec<String>, // pem files trusted on top of the webpki roots
+    pub pinned_certs: Vec<String>, // "sha256/<base64>" of accepted leaf certificates
+}
+
+impl RelayClientConfig {
+
+    pub fn for_relay(relay: u8) -> Self {
+        dotenv().ok();
+        RelayClientConfig {
+            timeout: Duration::from_secs(10),
+            connect_timeout: Duration::from_secs(3),
+            pool_idle_timeout: Duration::from_secs(300),
+            pool_max_idle_per_host: 32,
+            tcp_keepalive: Duration::from_secs(15),
+            http2_prior_knowledge: false,
+            local_address: env::var("RELAY_LOCAL_ADDRESS_RUST").ok().and_then(|addr| addr.parse().ok()),
+            interface: env::var("RELAY_INTERFACE_RUST").ok(),
+            scheme: default_scheme(relay).to_string(),
+            auth_in_header: true,
+            ca_cert_paths: vec![],
+            pinned_certs: vec![],
+        }
+    }
+
+    pub fn from_node(relay: u8, node: &RelayClientConfig4Node) -> Result<Self> {
+        let defaults = RelayClientConfig::for_relay(relay);
+        let millis = |ms: Option<u32>, default: Duration| ms.map(|ms| Duration::from_millis(ms as u64)).unwrap_or(default);
+
+        let scheme = match node.scheme.as_deref() {
+            Some(scheme @ ("http" | "https")) => scheme.to_string(),
+            Some(scheme) => return Err(anyhow!("unsupported scheme {:?}, expected http or https", scheme)),
+            None => defaults.scheme,
+        };
+
+        Ok(RelayClientConfig {
+            timeout: millis(node.timeout_ms, defaults.timeout),
+            connect_timeout: millis(node.connect_timeout_ms, defaults.connect_timeout),
+            pool_idle_timeout: millis(node.pool_idle_timeout_ms, defaults.pool_idle_timeout),
+            pool_max_idle_per_host: node.pool_max_idle_per_host
+                .map(|max| max as usize)
+                .unwrap_or(defaults.pool_max_idle_per_host),
+            tcp_keepalive: millis(node.tcp_keepalive_ms, defaults.tcp_keepalive),
+            http2_prior_knowledge: node.http2_prior_knowledge.unwrap_or(defaults.http2_prior_knowledge),
+            local_address: match &node.local_address {
+                Some(addr) => Some(addr.parse().map_err(|e| anyhow!("invalid local address {:?}: {}", addr, e))?),
+                None => defaults.local_address,
+            },
+            interface: node.interface.clone().or(defaults.interface),
+            scheme,
+            auth_in_header: node.auth_in_header.unwrap_or(defaults.auth_in_header),
+            ca_cert_paths: node.ca_cert_paths.clone().unwrap_or_default(),
+            pinned_certs: node.pinned_certs.clone().unwrap_or_default(),
+        })
+    }
+
+    // an explicit local address wins, otherwise the interface's own address
+    fn bind_address(&self) -> Result<Option<IpAddr>> {
+        match (&self.local_address, &self.interface) {
+            (Some(addr), _) => Ok(Some(*addr)),
+            (None, Some(interface)) => Ok(Some(interface_address(interface)?)),
+            (None, None) => Ok(None),
+        }
+    }
+
+    pub fn build(&self) -> Result<Client> {
+        let mut builder = Client::builder()
+            .timeout(self.timeout)
+            .connect_timeout(self.connect_timeout)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .tcp_keepalive(self.tcp_keepalive)
+            .tcp_nodelay(true)
+            .local_address(self.bind_address()?);
+
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+
+        if !self.ca_cert_paths.is_empty() || !self.pinned_certs.is_empty() {
+            let tls = build_tls_config(&self.ca_cert_paths, &self.pinned_certs, self.http2_prior_knowledge)?;
+            builder = builder.use_preconfigured_tls(tls);
+        }
+
+        builder.build().map_err(|e| anyhow!("Failed to create HTTP client: {}", e))
+    }
+}
+
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct RelayClientConfig4Node {
+    pub relay: String,
+    pub timeout_ms: Option<u32>,
+    pub connect_timeout_ms: Option<u32>,
+    pub pool_idle_timeout_ms: Option<u32>,
+    pub pool_max_idle_per_host: Option<u32>,
+    pub tcp_keepalive_ms: Option<u32>,
+    pub http2_prior_knowledge: Option<bool>,
+    pub local_address: Option<String>,
+    pub interface: Option<String>,
+    pub scheme: Option<String>,
+    pub auth_in_header: Option<bool>,
+    pub ca_cert_paths: Option<Vec<String>>,
+    pub pinned_certs: Option<Vec<String>>,
+}
+
+// reqwest can't bind to a device, so connections leave from the interface's address instead
+#[cfg(unix)]
+fn interface_address(interface: &str) -> Result<IpAddr> {
+    let addresses: Vec<IpAddr> = nix::ifaddrs::getifaddrs()
+        .map_err(|e| anyhow!("failed to list network interfaces: {}", e))?
+        .filter(|ifaddr| ifaddr.interface_name == interface)
+        .filter_map(|ifaddr| ifaddr.address)
+        .filter_map(|address| match (address.as_sockaddr_in(), address.as_sockaddr_in6()) {
+            (Some(v4), _) => Some(IpAddr::V4(v4.ip())),
+            (None, Some(v6)) => Some(IpAddr::V6(v6.ip())),
+            _ => None,
+        })
+        .collect();
+
+    // prefer ipv4, the relays are reached over it
+    addresses.iter().find(|addr| addr.is_ipv4())
+        .or_else(|| addresses.first())
+        .copied()
+        .ok_or_else(|| anyhow!("interface {} has no ip address", interface))
+}
+
+#[cfg(not(unix))]
+fn interface_address(interface: &str) -> Result<IpAddr> {
+    Err(anyhow!("binding to interface {} is not supported on this platform", interface))
+}
+
+// jito has always been https, the other relays were submitted to over plain http
+fn default_scheme(relay: u8) -> &'static str {
+    match relay {
+        SENDER_JITO => "https",
+        _ => "http",
+    }
+}
+
+// header a relay reads its key from. temporal only takes the key as the `c` query parameter
+fn auth_header_name(relay: u8) -> Option<&'static str> {
+    match relay {
+        SENDER_JITO => Some("x-jito-auth"),
+        SENDER_BLOXROUTE | SENDER_NEXT_BLOCK => Some("Authorization"),
+        SENDER_SLOT0_TRADE | SENDER_NODE1_ME => Some("api-key"),
+        _ => None,
+    }
+}
+
+fn auth_query_name(relay: u8) -> Option<&'static str> {
+    match relay {
+        SENDER_SLOT0_TRADE => Some("api-key"),
+        SENDER_TEMPORAL => Some("c"),
+        _ => None,
+    }
+}
+
+struct RelayClient {
+    client: Client,
+    config: RelayClientConfig,
+}
+
+static RELAY_CLIENTS: OnceLock<RwLock<HashMap<u8, RelayClient>>> = OnceLock::new();
+static RELAY_LAST_USED: OnceLock<RwLock<HashMap<u8, Instant>>> = OnceLock::new();
+
+fn relay_clients() -> &'static RwLock<HashMap<u8, RelayClient>> {
+    RELAY_CLIENTS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+fn relay_last_used() -> &'static RwLock<HashMap<u8, Instant>> {
+    RELAY_LAST_USED.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+// time since the relay's client last sent anything, None if it never did
+pub fn relay_idle_for(relay: u8) -> Option<Duration> {
+    relay_last_used().read().unwrap().get(&relay).map(|last| last.elapsed())
+}
+
+fn with_relay_client<T>(relay: u8, f: impl FnOnce(&RelayClient) -> T) -> Result<T> {
+    if let Some(relay_client) = relay_clients().read().unwrap().get(&relay) {
+        return Ok(f(relay_client));
+    }
+
+    let config = RelayClientConfig::for_relay(relay);
+    let client = config.build()
+        .map_err(|e| anyhow!("failed to create the {} HTTP client: {}", get_sender_name(relay), e))?;
+    let mut clients = relay_clients().write().unwrap();
+    Ok(f(clients.entry(relay).or_insert(RelayClient { client, config })))
+}
+
+// the relay's client, built with the default config on first use
+pub fn get_relay_client(relay: u8) -> Result<Client> {
+    relay_last_used().write().unwrap().insert(relay, Instant::now());
+    with_relay_client(relay, |relay_client| relay_client.client.clone())
+}
+
+pub fn get_relay_config(relay: u8) -> Result<RelayClientConfig> {
+    with_relay_client(relay, |relay_client| relay_client.config.clone())
+}
+
+// replace the relay's client, its pooled connections are dropped
+pub fn configure_relay_client(relay: u8, config: &RelayClientConfig) -> Result<()> {
+    let client = config.build()?;
+    relay_clients().write().unwrap().insert(relay, RelayClient { client, config: config.clone() });
+    Ok(())
+}
+
+// full url and auth headers for a request to `api_url` (a host, or a url with its own scheme).
+// the key goes in a header when the relay reads one, otherwise in its query parameter
+pub fn relay_request(relay: u8, api_url: &str, path: &str, auth_key: &str) -> Result<(String, HeaderMap)> {
+    let config = get_relay_config(relay)?;
+    let mut url = if api_url.starts_with("http://") || api_url.starts_with("https://") {
+        format!("{}{}", api_url.trim_end_matches('/'), path)
+    } else {
+        format!("{}://{}{}", config.scheme, api_url.trim_end_matches('/'), path)
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    if auth_key.is_empty() {
+        return Ok((url, headers));
+    }
+
+    let header_name = auth_header_name(relay)
+        .filter(|_| config.auth_in_header || auth_query_name(relay).is_none());
+    match (header_name, auth_query_name(relay)) {
+        (Some(name), _) => {
+            headers.insert(name, HeaderValue::from_str(auth_key)
+                .map_err(|e| anyhow!("Invalid auth header: {}", e))?);
+        },
+        (None, Some(name)) => {
+            let separator = if url.contains('?') { '&' } else { '?' };
+            url = format!("{}{}{}={}", url, separator, name, auth_key);
+        },
+        (None, None) => {},
+    }
+    Ok((url, headers))
+}
+
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct PrewarmResult {
+    pub relay: String,
+    pub url: String,
+    pub cold_ms: Option<u32>,  // first request, includes dns, tcp and tls setup
+    pub warm_ms: Option<u32>,  // second request over the pooled connection
+    pub setup_ms: Option<u32>, // cold - warm, the reconnect cost a trade pays on an idle pool
+    pub error: Option<String>,
+}
+
+// open pooled connections to the endpoints and measure what connection setup costs.
+// the keep-alive service keeps them open afterwards
+pub async fn prewarm_relay(relay: u8, url: &str) -> PrewarmResult {
+    let mut result = PrewarmResult {
+        relay: get_sender_name(relay).to_string(),
+        url: redact_url(url),
+        cold_ms: None,
+        warm_ms: None,
+        setup_ms: None,
+        error: None,
+    };
+
+    let auth_key = get_relay_key(relay);
+    let cold_start = Instant::now();
+    let cold_result = ping_relay(relay, url, auth_key).await;
+    let cold = cold_start.elapsed();
+    record_ping(relay, url, &cold_result, cold);
+    if let Err(e) = cold_result {
+        result.error = Some(e.to_string());
+        return result;
+    }
+
+    let warm_start = Instant::now();
+    let warm_result = ping_relay(relay, url, auth_key).await;
+    let warm = warm_start.elapsed();
+    record_ping(relay, url, &warm_result, warm);
+    if let Err(e) = warm_result {
+        result.error = Some(e.to_string());
+    }
+
+    result.cold_ms = Some(cold.as_millis() as u32);
+    result.warm_ms = Some(warm.as_millis() as u32);
+    result.setup_ms = Some(cold.saturating_sub(warm).as_millis() as u32);
+    result
+}
+
+// prewarm the endpoints trades would use for `relays`. with `idle` set, only relays whose
+// client has been quiet at least that long (or never used) are touched
+pub async fn prewarm_relays(relays: &[u8], idle: Option<Duration>, region_count: usize) -> Vec<PrewarmResult> {
+    let targets: Vec<(u8, String)> = relays.iter()
+        .filter(|relay| match (idle, relay_idle_for(**relay)) {
+            (Some(idle), Some(idle_for)) => idle_for >= idle,
+            _ => true,
+        })
+        .flat_map(|relay| resolve_relay_urls(*relay, None, region_count)
+            .into_iter()
+            .map(move |url| (*relay, url)))
+        .collect();
+
+    let results = join_all(targets.iter().map(|(relay, url)| prewarm_relay(*relay, url))).await;
+    for result in &results {
+        match (&result.error, result.setup_ms) {
+            (Some(e), _) => warn!("prewarm {} {} failed: {}", result.relay, result.url, e),
+            (None, Some(setup_ms)) => info!("prewarm {} {} cold {:?}ms, connection setup {}ms",
+                result.relay, result.url, result.cold_ms, setup_ms),
+            _ => {},
+        }
+    }
+    results
+}
@@ -0,0 +1,288 @@
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, bail, Result};
+use base64::{Engine as _, engine::general_purpose};
+use napi_derive::napi;
+use pbkdf2::pbkdf2_hmac;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::sync::{Arc, OnceLock, RwLock};
+use zeroize::Zeroizing;
+
+use crate::secret::Secret;
+
+// wallets loaded once and referred to by label (`wallet_id` in the trade params), so raw
+// secrets don't have to cross from node on every call. sources are solana cli keypair files
+// (json array of 64 bytes) and password encrypted keystores (pbkdf2-sha256 + aes-256-gcm).
+// key bytes live in zeroizing buffers and are wiped when a wallet is unloaded or replaced
+
+const KEYSTORE_VERSION: u32 = 1;
+const KEYSTORE_KDF: &str = "pbkdf2-sha256";
+const KEYSTORE_CIPHER: &str = "aes-256-gcm";
+const PBKDF2_ITERATIONS: u32 = 600_000;
+// accepted when reading a keystore: weak files are refused, and a hostile count can't stall the process
+const MIN_PBKDF2_ITERATIONS: u32 = 10_000;
+const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+pub struct ManagedKey {
+    secret: Zeroizing<Vec<u8>>, // 64 byte ed25519 keypair
+    pubkey: Pubkey,
+}
+
+impl ManagedKey {
+
+    fn from_bytes(bytes: Zeroizing<Vec<u8>>) -> Result<Self> {
+        if bytes.len() != 64 {
+            bail!("expected a 64 byte keypair, got {} bytes", bytes.len());
+        }
+        let keypair = Keypair::from_bytes(&bytes)
+            .map_err(|e| anyhow!("invalid keypair: {}", e))?;
+        Ok(ManagedKey {
+            pubkey: keypair.pubkey(),
+            secret: bytes,
+        })
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    pub fn keypair(&self) -> Keypair {
+        Keypair::from_bytes(&self.secret).expect("validated when loaded")
+    }
+}
+
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct WalletInfo {
+    pub label: String,
+    pub public_key: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeystoreFile {
+    version: u32,
+    public_key: String,
+    kdf: String,
+    iterations: u32,
+    salt: String,
+    cipher: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+static WALLETS: OnceLock<RwLock<HashMap<String, Arc<ManagedKey>>>> = OnceLock::new();
+
+fn wallets() -> &'static RwLock<HashMap<String, Arc<ManagedKey>>> {
+    WALLETS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+fn insert_wallet(label: &str, key: ManagedKey) -> Pubkey {
+    let pubkey = key.pubkey();
+    wallets().write().unwrap().insert(label.to_string(), Arc::new(key));
+    pubkey
+}
+
+fn decode_base58_secret(secret_key: &str) -> Result<Zeroizing<Vec<u8>>> {
+    let bytes = bs58::decode(secret_key.trim())
+        .into_vec()
+        .map_err(|_| anyhow!("secret key is not valid base58"))?;
+    Ok(Zeroizing::new(bytes))
+}
+
+// base58 secret as exported by phantom / solana-keygen, without the panic of `from_base58_string`
+pub fn parse_secret_key(secret_key: &str) -> Result<Keypair> {
+    Ok(ManagedKey::from_bytes(decode_base58_secret(secret_key)?)?.keypair())
+}
+
+fn read_keypair_json(json: &str) -> Result<Zeroizing<Vec<u8>>> {
+    let bytes: Vec<u8> = serde_json::from_str(json)
+        .map_err(|_| anyhow!("not a solana keypair file, expected a json array of 64 bytes"))?;
+    Ok(Zeroizing::new(bytes))
+}
+
+fn derive_key(password: &str, salt: &[u8], iterations: u32) -> Zeroizing<[u8; 32]> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, &mut key[..]);
+    key
+}
+
+fn decrypt_keystore(json: &str, password: &str) -> Result<Zeroizing<Vec<u8>>> {
+    let keystore: KeystoreFile = serde_json::from_str(json)
+        .map_err(|e| anyhow!("invalid keystore file: {}", e))?;
+    if keystore.version != KEYSTORE_VERSION || keystore.kdf != KEYSTORE_KDF || keystore.cipher != KEYSTORE_CIPHER {
+        bail!("unsupported keystore v{} {} / {}", keystore.version, keystore.kdf, keystore.cipher);
+    }
+    if !(MIN_PBKDF2_ITERATIONS..=MAX_PBKDF2_ITERATIONS).contains(&keystore.iterations) {
+        bail!("keystore iterations {} outside {}..={}", keystore.iterations, MIN_PBKDF2_ITERATIONS, MAX_PBKDF2_ITERATIONS);
+    }
+
+    let salt = general_purpose::STANDARD.decode(&keystore.salt)?;
+    let nonce = general_purpose::STANDARD.decode(&keystore.nonce)?;
+    let ciphertext = general_purpose::STANDARD.decode(&keystore.ciphertext)?;
+    if nonce.len() != NONCE_LEN {
+        bail!("invalid keystore nonce");
+    }
+
+    let key = derive_key(password, &salt, keystore.iterations);
+    let cipher = Aes256Gcm::new_from_slice(&key[..])
+        .map_err(|e| anyhow!("keystore cipher: {}", e))?;
+    let plaintext = cipher.decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| anyhow!("wrong password or corrupted keystore"))?;
+    let plaintext = Zeroizing::new(plaintext);
+
+    let key = ManagedKey::from_bytes(plaintext.clone())?;
+    if key.pubkey().to_string() != keystore.public_key {
+        bail!("keystore public key does not match its contents");
+    }
+    Ok(plaintext)
+}
+
+// load a solana cli keypair file, or an encrypted keystore when `password` is given
+pub fn load_wallet(label: &str, path: &str, password: Option<&Secret<String>>) -> Result<Pubkey> {
+    let json = Zeroizing::new(fs::read_to_string(path)
+        .map_err(|e| anyhow!("failed to read {}: {}", path, e))?);
+    let bytes = match password {
+        Some(password) => decrypt_keystore(&json, password.expose())?,
+        None => read_keypair_json(&json)?,
+    };
+    Ok(insert_wallet(label, ManagedKey::from_bytes(bytes)?))
+}
+
+pub fn import_wallet(label: &str, secret_key: &Secret<String>) -> Result<Pubkey> {
+    let bytes = decode_base58_secret(secret_key.expose())?;
+    Ok(insert_wallet(label, ManagedKey::from_bytes(bytes)?))
+}
+
+// write a loaded wallet to `path` as an encrypted keystore
+pub fn save_encrypted_wallet(label: &str, path: &str, password: &Secret<String>) -> Result<()> {
+    let key = get_wallet(label)?;
+
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let derived = derive_key(password.expose(), &salt, PBKDF2_ITERATIONS);
+    let cipher = Aes256Gcm::new_from_slice(&derived[..])
+        .map_err(|e| anyhow!("keystore cipher: {}", e))?;
+    let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce), key.secret.as_slice())
+        .map_err(|_| anyhow!("failed to encrypt keystore"))?;
+
+    let keystore = KeystoreFile {
+        version: KEYSTORE_VERSION,
+        public_key: key.pubkey().to_string(),
+        kdf: KEYSTORE_KDF.to_string(),
+        iterations: PBKDF2_ITERATIONS,
+        salt: general_purpose::STANDARD.encode(salt),
+        cipher: KEYSTORE_CIPHER.to_string(),
+        nonce: general_purpose::STANDARD.encode(nonce),
+        ciphertext: general_purpose::STANDARD.encode(ciphertext),
+    };
+
+    let tmp_path = format!("{}.tmp", path);
+    let _ = fs::remove_file(&tmp_path);
+    let mut file = owner_only_file(&tmp_path)?;
+    file.write_all(serde_json::to_string_pretty(&keystore)?.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+// created 0600 so the keystore is never readable by other users, not even between write and chmod
+#[cfg(unix)]
+fn owner_only_file(path: &str) -> Result<fs::File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    Ok(fs::OpenOptions::new().write(true).create_new(true).mode(0o600).open(path)?)
+}
+
+#[cfg(not(unix))]
+fn owner_only_file(path: &str) -> Result<fs::File> {
+    Ok(fs::OpenOptions::new().write(true).create_new(true).open(path)?)
+}
+
+pub fn get_wallet(label: &str) -> Result<Arc<ManagedKey>> {
+    wallets().read().unwrap()
+        .get(label)
+        .cloned()
+        .ok_or_else(|| anyhow!("wallet {:?} is not loaded", label))
+}
+
+pub fn unload_wallet(label: &str) -> bool {
+    wallets().write().unwrap().remove(label).is_some()
+}
+
+pub fn list_wallets() -> Vec<WalletInfo> {
+    let mut list: Vec<WalletInfo> = wallets().read().unwrap()
+        .iter()
+        .map(|(label, key)| WalletInfo {
+            label: label.clone(),
+            public_key: key.pubkey().to_string(),
+        })
+        .collect();
+    list.sort_by(|a, b| a.label.cmp(&b.label));
+    list
+}
+
+// the signer of a trade: the loaded wallet `wallet_id`, else the raw base58 secret
+pub fn resolve_signer(wallet_id: Option<&str>, secret_key: &Secret<String>) -> Result<Keypair> {
+    match wallet_id {
+        Some(label) => Ok(get_wallet(label)?.keypair()),
+        None if secret_key.expose().is_empty() => bail!("either wallet_id or secret_key is required"),
+        None => parse_secret_key(secret_key.expose()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("keystore-{}-{}.json", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn saved_keystore(label: &str) -> (String, KeystoreFile) {
+        let keypair = Keypair::new();
+        import_wallet(label, &Secret::new(keypair.to_base58_string())).unwrap();
+        let path = temp_path(label);
+        save_encrypted_wallet(label, &path, &Secret::new("hunter2".to_string())).unwrap();
+        let keystore = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        (path, keystore)
+    }
+
+    #[test]
+    fn round_trips_and_rejects_out_of_range_iterations() {
+        let (path, mut keystore) = saved_keystore("iterations");
+        let json = fs::read_to_string(&path).unwrap();
+        assert!(decrypt_keystore(&json, "hunter2").is_ok());
+        assert!(decrypt_keystore(&json, "wrong").is_err());
+
+        for iterations in [0, MIN_PBKDF2_ITERATIONS - 1, MAX_PBKDF2_ITERATIONS + 1, u32::MAX] {
+            keystore.iterations = iterations;
+            let err = decrypt_keystore(&serde_json::to_string(&keystore).unwrap(), "hunter2").unwrap_err();
+            assert!(err.to_string().contains("iterations"), "{}", err);
+        }
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keystore_file_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+        let (path, _) = saved_keystore("mode");
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        fs::remove_file(path).unwrap();
+    }
+}
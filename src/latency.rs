@@ -0,0 +1,109 @@
+use napi_derive::napi;
+use std::cell::RefCell;
+use std::future::Future;
+use std::time::Duration;
+
+use crate::metrics::observe_trade_stage;
+use crate::secret::redact_url;
+
+// per-call latency budget of a trade. stages record into a task local while `measure` runs,
+// so the deep rpc / blockhash / sign calls don't need a timings argument threaded through
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Parse,
+    Build,
+    Rpc,
+    Blockhash,
+    Sign,
+    Send,
+}
+
+impl Stage {
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Parse => "parse",
+            Stage::Build => "build",
+            Stage::Rpc => "rpc",
+            Stage::Blockhash => "blockhash",
+            Stage::Sign => "sign",
+            Stage::Send => "send",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct RelayLatency {
+    pub relay: String,
+    pub url: String,
+    pub http_ms: f64,
+}
+
+// milliseconds. build includes the rpc lookups it makes, which are also counted in rpc_ms;
+// sign excludes the blockhash fetch. send is the wall time of the concurrent relay requests
+#[derive(Debug, Clone, Default)]
+#[napi(object)]
+pub struct LatencyBreakdown {
+    pub parse_ms: f64,
+    pub build_ms: f64,
+    pub rpc_ms: f64,
+    pub blockhash_ms: f64,
+    pub sign_ms: f64,
+    pub send_ms: f64,
+    pub relay_http: Vec<RelayLatency>,
+    pub total_ms: f64,
+}
+
+tokio::task_local! {
+    static TRADE_LATENCY: RefCell<LatencyBreakdown>;
+}
+
+fn as_ms(elapsed: Duration) -> f64 {
+    elapsed.as_secs_f64() * 1000.0
+}
+
+// run `future` collecting the stages it records
+pub async fn measure<F: Future>(future: F) -> (F::Output, LatencyBreakdown) {
+    TRADE_LATENCY.scope(RefCell::new(LatencyBreakdown::default()), async move {
+        let output = future.await;
+        let latency = TRADE_LATENCY.with(|latency| latency.borrow().clone());
+        (output, latency)
+    }).await
+}
+
+// add to the stage of the trade being measured and to the stage histogram
+pub fn record_stage(stage: Stage, elapsed: Duration) {
+    observe_trade_stage(stage.name(), elapsed);
+    let _ = TRADE_LATENCY.try_with(|latency| {
+        let mut latency = latency.borrow_mut();
+        let ms = as_ms(elapsed);
+        match stage {
+            Stage::Parse => latency.parse_ms += ms,
+            Stage::Build => latency.build_ms += ms,
+            Stage::Rpc => latency.rpc_ms += ms,
+            Stage::Blockhash => latency.blockhash_ms += ms,
+            Stage::Sign => latency.sign_ms += ms,
+            Stage::Send => latency.send_ms += ms,
+        }
+    });
+}
+
+pub fn record_relay_http(relay: &str, url: &str, elapsed: Duration) {
+    let _ = TRADE_LATENCY.try_with(|latency| {
+        latency.borrow_mut().relay_http.push(RelayLatency {
+            relay: relay.to_string(),
+            url: redact_url(url),
+            http_ms: as_ms(elapsed),
+        });
+    });
+}
+
+// time a blocking rpc call
+pub fn timed_rpc<T>(call: impl FnOnce() -> T) -> T {
+    let start = std::time::Instant::now();
+    let result = call();
+    record_stage(Stage::Rpc, start.elapsed());
+    result
+}
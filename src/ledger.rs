@@ -0,0 +1,378 @@
+use anyhow::{anyhow, Result};
+use dotenv::dotenv;
+use napi_derive::napi;
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_program::pubkey::Pubkey;
+
+use std::collections::{BTreeMap, HashSet};
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::str::FromStr;
+use std::sync::Mutex;
+use tracing::warn;
+
+use crate::bonding_curve_cache::get_bonding_curve;
+use crate::contracts::pumpfun_global::get_fee_params;
+use crate::fill_parser::RealizedFill;
+
+// append-only jsonl ledger of realized fills, positions are rebuilt by replaying it.
+// cost basis is average cost in lamports and includes fee and tip, rent is kept apart
+
+const DEFAULT_LEDGER_PATH: &str = "ledger.jsonl";
+
+static LEDGER_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LedgerEntry {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub wallet: String,
+    pub mint: String,
+    pub is_buy: bool,
+    pub token_amount: u64,
+    pub sol_amount: u64, // lamports into or out of the swap
+    pub fee: u64,
+    pub tip: u64,
+    pub rent: i64,
+}
+
+impl LedgerEntry {
+
+    // None for failed transactions and ones that did not move a token
+    pub fn from_fill(fill: &RealizedFill) -> Option<Self> {
+        if fill.error.is_some() || fill.token_delta == 0 {
+            return None;
+        }
+        Some(LedgerEntry {
+            signature: fill.signature.clone(),
+            slot: fill.slot,
+            block_time: fill.block_time,
+            wallet: fill.wallet.to_string(),
+            mint: fill.mint?.to_string(),
+            is_buy: fill.is_buy.unwrap_or(fill.token_delta > 0),
+            token_amount: fill.token_delta.unsigned_abs() as u64,
+            sol_amount: fill.swap_sol_delta.unsigned_abs() as u64,
+            fee: fill.fee,
+            tip: fill.tip,
+            rent: fill.rent as i64,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Position {
+    pub wallet: String,
+    pub mint: String,
+    pub token_amount: u64,
+    pub cost_basis: u64,
+    pub realized_pnl: i128,
+    pub fees: u64,
+    pub tips: u64,
+    pub rent: i64,
+    pub buys: u32,
+    pub sells: u32,
+}
+
+impl Position {
+
+    pub fn apply(&mut self, entry: &LedgerEntry) {
+        self.fees += entry.fee;
+        self.tips += entry.tip;
+        self.rent += entry.rent;
+
+        if entry.is_buy {
+            self.buys += 1;
+            self.token_amount += entry.token_amount;
+            self.cost_basis += entry.sol_amount + entry.fee + entry.tip;
+        }
+        else {
+            self.sells += 1;
+            //a sell larger than the position (tokens that came from outside the ledger) only
+            //realizes the share of proceeds for the tokens this position holds
+            let sold = entry.token_amount.min(self.token_amount);
+            let cost_of_sold = if self.token_amount == 0 {
+                0
+            } else {
+                (self.cost_basis as u128 * sold as u128 / self.token_amount as u128) as u64
+            };
+            let net_proceeds = entry.sol_amount as i128 - entry.fee as i128 - entry.tip as i128;
+            let proceeds = if sold == entry.token_amount {
+                net_proceeds
+            } else {
+                net_proceeds * sold as i128 / entry.token_amount as i128
+            };
+            self.realized_pnl += proceeds - cost_of_sold as i128;
+            self.cost_basis -= cost_of_sold;
+            self.token_amount -= sold;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct Position4Node {
+    pub wallet: String,
+    pub mint: String,
+    pub token_amount: String,
+    pub cost_basis: String,
+    pub realized_pnl: String,
+    pub mark_value: Option<String>, // curve quote for selling the whole position, none once migrated
+    pub unrealized_pnl: Option<String>,
+    pub fees: String,
+    pub tips: String,
+    pub rent: String,
+    pub buys: u32,
+    pub sells: u32,
+}
+
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct WalletPnl4Node {
+    pub wallet: String,
+    pub realized_pnl: String,
+    pub unrealized_pnl: String,
+    pub cost_basis: String,
+    pub positions: Vec<Position4Node>,
+}
+
+pub fn ledger_path() -> String {
+    dotenv().ok();
+    env::var("LEDGER_PATH_RUST").unwrap_or_else(|_| DEFAULT_LEDGER_PATH.to_string())
+}
+
+pub fn load_entries(path: &str) -> Result<Vec<LedgerEntry>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(anyhow!("Failed to open ledger {}: {}", path, e)),
+    };
+
+    let mut entries = vec![];
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<LedgerEntry>(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => warn!("ledger {} line {} skipped: {}", path, number + 1, e),
+        }
+    }
+    Ok(entries)
+}
+
+// append the fill unless it is already recorded, returns whether it was written
+pub fn record_fill(fill: &RealizedFill) -> Result<bool> {
+    let Some(entry) = LedgerEntry::from_fill(fill) else {
+        return Ok(false);
+    };
+
+    let path = ledger_path();
+    let _guard = LEDGER_LOCK.lock().unwrap();
+
+    if load_entries(&path)?.iter().any(|e| e.signature == entry.signature && e.wallet == entry.wallet) {
+        return Ok(false);
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+    Ok(true)
+}
+
+pub fn build_positions(entries: &[LedgerEntry]) -> Vec<Position> {
+    let mut positions: BTreeMap<(String, String), Position> = BTreeMap::new();
+    let mut seen = HashSet::new();
+
+    let mut ordered: Vec<&LedgerEntry> = entries.iter().collect();
+    ordered.sort_by_key(|entry| entry.slot);
+
+    for entry in ordered {
+        if !seen.insert((entry.signature.clone(), entry.wallet.clone())) {
+            continue;
+        }
+        positions
+            .entry((entry.wallet.clone(), entry.mint.clone()))
+            .or_insert_with(|| Position {
+                wallet: entry.wallet.clone(),
+                mint: entry.mint.clone(),
+                ..Position::default()
+            })
+            .apply(entry);
+    }
+
+    positions.into_values().collect()
+}
+
+// lamports the position would fetch on the curve right now
+fn mark_value(rpc_client: &RpcClient, position: &Position, fee_bps: u64) -> Option<u64> {
+    if position.token_amount == 0 {
+        return Some(0);
+    }
+    let mint = Pubkey::from_str(&position.mint).ok()?;
+    match get_bonding_curve(rpc_client, &mint) {
+        Ok(Some(curve)) if !curve.complete => Some(curve.get_sell_sol_amount(position.token_amount, fee_bps)),
+        _ => None,
+    }
+}
+
+// pnl per wallet with its positions, optionally narrowed to one wallet and/or mint
+pub fn get_pnl(
+    rpc_client: &RpcClient,
+    wallet: Option<&str>,
+    mint: Option<&str>,
+) -> Result<Vec<WalletPnl4Node>> {
+    let entries = load_entries(&ledger_path())?;
+    let (_, fee_bps) = get_fee_params(rpc_client);
+
+    let mut wallets: BTreeMap<String, WalletPnl4Node> = BTreeMap::new();
+    let mut totals: BTreeMap<String, (i128, i128, u64)> = BTreeMap::new();
+
+    for position in build_positions(&entries) {
+        if wallet.is_some_and(|w| w != position.wallet) || mint.is_some_and(|m| m != position.mint) {
+            continue;
+        }
+
+        let mark = mark_value(rpc_client, &position, fee_bps);
+        let unrealized = mark.map(|mark| mark as i128 - position.cost_basis as i128);
+
+        let total = totals.entry(position.wallet.clone()).or_insert((0, 0, 0));
+        total.0 += position.realized_pnl;
+        total.1 += unrealized.unwrap_or(0);
+        total.2 += position.cost_basis;
+
+        wallets
+            .entry(position.wallet.clone())
+            .or_insert_with(|| WalletPnl4Node {
+                wallet: position.wallet.clone(),
+                realized_pnl: "0".to_string(),
+                unrealized_pnl: "0".to_string(),
+                cost_basis: "0".to_string(),
+                positions: vec![],
+            })
+            .positions
+            .push(Position4Node {
+                wallet: position.wallet.clone(),
+                mint: position.mint.clone(),
+                token_amount: position.token_amount.to_string(),
+                cost_basis: position.cost_basis.to_string(),
+                realized_pnl: position.realized_pnl.to_string(),
+                mark_value: mark.map(|mark| mark.to_string()),
+                unrealized_pnl: unrealized.map(|pnl| pnl.to_string()),
+                fees: position.fees.to_string(),
+                tips: position.tips.to_string(),
+                rent: position.rent.to_string(),
+                buys: position.buys,
+                sells: position.sells,
+            });
+    }
+
+    Ok(wallets.into_iter()
+        .map(|(wallet, mut pnl)| {
+            let (realized, unrealized, cost_basis) = totals[&wallet];
+            pnl.realized_pnl = realized.to_string();
+            pnl.unrealized_pnl = unrealized.to_string();
+            pnl.cost_basis = cost_basis.to_string();
+            pnl
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bonding_curve_cache::{get_bonding_curve_pda, update_bonding_curve};
+    use crate::contracts::pumpfun_proxy::BondingCurveInfo;
+
+    fn entry(signature: &str, slot: u64, is_buy: bool, token_amount: u64, sol_amount: u64) -> LedgerEntry {
+        LedgerEntry {
+            signature: signature.to_string(),
+            slot,
+            block_time: None,
+            wallet: "wallet".to_string(),
+            mint: "mint".to_string(),
+            is_buy,
+            token_amount,
+            sol_amount,
+            fee: 5_000,
+            tip: 1_000,
+            rent: 0,
+        }
+    }
+
+    #[test]
+    fn sells_realize_against_average_cost() {
+        let mut position = Position::default();
+        position.apply(&entry("a", 1, true, 1_000, 1_000_000));
+        position.apply(&entry("b", 2, true, 1_000, 2_000_000));
+        assert_eq!(position.token_amount, 2_000);
+        assert_eq!(position.cost_basis, 3_012_000);
+
+        position.apply(&entry("c", 3, false, 500, 900_000));
+        // half of a quarter of the cost: 753_000, proceeds net of fee and tip: 894_000
+        assert_eq!(position.token_amount, 1_500);
+        assert_eq!(position.cost_basis, 2_259_000);
+        assert_eq!(position.realized_pnl, 894_000 - 753_000);
+        assert_eq!((position.buys, position.sells), (2, 1));
+        assert_eq!((position.fees, position.tips), (15_000, 3_000));
+    }
+
+    #[test]
+    fn oversized_sells_are_clamped_to_the_position() {
+        let mut position = Position::default();
+        position.apply(&entry("a", 1, true, 1_000, 1_000_000));
+        position.apply(&entry("b", 2, false, 4_000, 4_006_000));
+        // only a quarter of the sell belongs to this position
+        assert_eq!(position.token_amount, 0);
+        assert_eq!(position.cost_basis, 0);
+        assert_eq!(position.realized_pnl, 1_000_000 - 1_006_000);
+
+        // nothing left to sell, nothing realized
+        position.apply(&entry("c", 3, false, 1_000, 1_000_000));
+        assert_eq!(position.realized_pnl, 1_000_000 - 1_006_000);
+        assert_eq!(position.sells, 2);
+    }
+
+    #[test]
+    fn positions_replay_in_slot_order_without_duplicates() {
+        let entries = vec![
+            entry("b", 2, false, 1_000, 2_000_000),
+            entry("a", 1, true, 1_000, 1_000_000),
+            entry("a", 1, true, 1_000, 1_000_000),
+        ];
+        let positions = build_positions(&entries);
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].token_amount, 0);
+        assert_eq!(positions[0].realized_pnl, 1_994_000 - 1_006_000);
+    }
+
+    #[test]
+    fn positions_are_marked_on_the_curve() {
+        // the cache answers for live curves, the unreachable rpc is only hit for the migrated one
+        let rpc_client = RpcClient::new("http://127.0.0.1:1".to_string());
+        let mint = Pubkey::new_unique();
+        let curve = BondingCurveInfo {
+            discriminator: 0,
+            virtual_token_reserves: 1_073_000_000_000_000,
+            virtual_sol_reserves: 30_000_000_000,
+            real_token_reserves: 793_100_000_000_000,
+            real_sol_reserves: 0,
+            token_total_supply: 1_000_000_000_000_000,
+            complete: false,
+            creator: Pubkey::default(),
+        };
+        update_bonding_curve(get_bonding_curve_pda(&mint).unwrap(), 1, curve.clone());
+
+        let mut position = Position { mint: mint.to_string(), ..Position::default() };
+        assert_eq!(mark_value(&rpc_client, &position, 100), Some(0));
+
+        position.token_amount = 34_281_150_129_545;
+        assert_eq!(mark_value(&rpc_client, &position, 100), Some(curve.get_sell_sol_amount(position.token_amount, 100)));
+        assert_eq!(mark_value(&rpc_client, &position, 100), Some(919_504_642));
+
+        update_bonding_curve(get_bonding_curve_pda(&mint).unwrap(), 2, BondingCurveInfo { complete: true, ..curve });
+        assert_eq!(mark_value(&rpc_client, &position, 100), None);
+    }
+}
@@ -6,16 +6,50 @@ pub mod configs;
 pub mod utils;
 pub mod transaction_builder;
 pub mod node_to_rust;
+pub mod seed_account;
+pub mod ata_cache;
+pub mod events;
+pub mod copy_trade;
+pub mod listener;
+pub mod bonding_curve_cache;
+pub mod fill_parser;
+pub mod ledger;
+pub mod exit_rules;
+pub mod region_probe;
+pub mod http_clients;
+pub mod tls;
+pub mod metrics;
+pub mod telemetry;
+pub mod latency;
+pub mod secret;
+pub mod keystore;
+#[cfg(feature = "geyser")]
+pub mod geyser;
 
 use napi_derive::napi;
 use solana_program::native_token::LAMPORTS_PER_SOL;
-use solana_sdk::signature::Keypair;
 use crate::transaction_builder::TransactionBuilder;
-use crate::utils::{get_client, SwapParam, SwapParam4Node};
-use crate::configs::global::{NATIVE_MINT};
+use crate::utils::{get_client, SwapParam, SwapParam4Node, LaunchParam, LaunchParam4Node, LaunchResult, BundleBuyParam, BundleBuyParam4Node, BundleBuyOutcome, SeedAccountParam, SeedAccountParam4Node, SeedAccountResult, CloseTokenAccountsResult, TradeReport};
+use crate::seed_account::derive_seed_account;
+use crate::keystore::{resolve_signer, WalletInfo};
+use crate::secret::Secret;
+use solana_sdk::signature::Signer;
+use solana_program::pubkey::Pubkey;
+use std::str::FromStr;
 use crate::node_to_rust::*;
 use crate::keep_alive::*;
 use napi::Error as NapiError;
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use crate::copy_trade::{CopyTradeConfig, CopyTradeEvent, parse_target};
+use crate::listener::PumpfunEvent4Node;
+use crate::exit_rules::{ExitRule, ExitRule4Node, ExitTriggerEvent};
+use crate::region_probe::RegionProbe4Node;
+use crate::http_clients::{RelayClientConfig, RelayClientConfig4Node, PrewarmResult};
+use crate::configs::bribe::parse_sender_name;
+use crate::fill_parser::{fetch_fill, RealizedFill4Node};
+use crate::ledger::{record_fill as record_ledger_fill, get_pnl as get_ledger_pnl, WalletPnl4Node};
+use solana_client::rpc_client::RpcClient;
+use std::sync::Arc;
 use std::time::Instant;
 use anchor_spl::token::accessor::authority;
 
@@ -29,22 +63,424 @@ pub async fn swap(
     let rust_param: SwapParam = param.into();
     let rpc_url = rust_param.connection.to_string();
 
-    let fee_payer = Keypair::from_base58_string(&rust_param.secret_key);
+    let fee_payer = rust_param.signer()
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
 
     // let amount = 100 * LAMPORTS_PER_SOL; // 1 SOL = 1,000,000,000 lamports
     // let amount = (0.002 * LAMPORTS_PER_SOL as f64) as u64; // 1 SOL = 1,000,000,000 lamports
 
-    println!("start...");
     let mut builder = TransactionBuilder::new(rpc_url, fee_payer);
 
     let response = builder.trade(&rust_param).await;
 
-    println!("rust cost {:?}", start.elapsed());
+    tracing::debug!(elapsed = ?start.elapsed(), "swap done");
 
     response.map_err(|e| NapiError::from_reason(e.to_string()))
 
 }
 
+// like `swap`, with the outcome of every relay endpoint, the signatures sent and a latency breakdown
+#[napi]
+pub async fn swap_detailed(
+    param: SwapParam4Node
+) -> Result<TradeReport, NapiError> {
+
+    let parse_start = Instant::now();
+    let rust_param: SwapParam = param.into();
+    let fee_payer = rust_param.signer()
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+    let parse_time = parse_start.elapsed();
+
+    let builder = TransactionBuilder::new(rust_param.connection.clone(), fee_payer);
+
+    //node param conversion happens before the trade's own measurement starts
+    let mut report = builder.trade_detailed(&rust_param).await
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+    let parse_ms = parse_time.as_secs_f64() * 1000.0;
+    report.latency.parse_ms += parse_ms;
+    report.latency.total_ms += parse_ms;
+    Ok(report)
+}
+
+#[napi]
+pub async fn launch(
+    param: LaunchParam4Node
+) -> Result<LaunchResult, NapiError> {
+
+    let rust_param: LaunchParam = param.into();
+    let rpc_url = rust_param.swap.connection.to_string();
+
+    let fee_payer = rust_param.swap.signer()
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+
+    let builder = TransactionBuilder::new(rpc_url, fee_payer);
+
+    let (mint, responses) = builder.launch(&rust_param).await
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+
+    Ok(LaunchResult {
+        mint,
+        responses,
+    })
+}
+
+#[napi]
+pub async fn bundle_buy(
+    param: BundleBuyParam4Node
+) -> Result<Vec<BundleBuyOutcome>, NapiError> {
+
+    let rust_param: BundleBuyParam = param.into();
+    let rpc_url = rust_param.swap.connection.to_string();
+
+    let fee_payer = rust_param.swap.signer()
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+
+    let builder = TransactionBuilder::new(rpc_url, fee_payer);
+
+    builder.bundle_buy(&rust_param).await
+        .map_err(|e| NapiError::from_reason(e.to_string()))
+}
+
+#[napi]
+pub fn get_seed_account(
+    public_key: String,
+    seed: String,
+) -> Result<String, NapiError> {
+    let base = Pubkey::from_str(&public_key)
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+    derive_seed_account(&base, &seed)
+        .map(|pubkey| pubkey.to_string())
+        .map_err(|e| NapiError::from_reason(e.to_string()))
+}
+
+#[napi]
+pub async fn create_seed_account(
+    param: SeedAccountParam4Node
+) -> Result<SeedAccountResult, NapiError> {
+
+    let rust_param: SeedAccountParam = param.into();
+    let fee_payer = rust_param.signer()
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+    let builder = TransactionBuilder::new(rust_param.connection.clone(), fee_payer);
+
+    let mint = Pubkey::from_str(&rust_param.mint)
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+    let (public_key, signature) = builder
+        .create_seed_account(&rust_param.seed, &mint, rust_param.lamports)
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+
+    Ok(SeedAccountResult {
+        public_key,
+        signature,
+    })
+}
+
+#[napi]
+pub async fn sweep_seed_account(
+    param: SeedAccountParam4Node
+) -> Result<SeedAccountResult, NapiError> {
+
+    let rust_param: SeedAccountParam = param.into();
+    let fee_payer = rust_param.signer()
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+    let public_key = derive_seed_account(&fee_payer.pubkey(), &rust_param.seed)
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+    let builder = TransactionBuilder::new(rust_param.connection.clone(), fee_payer);
+
+    let mint = Pubkey::from_str(&rust_param.mint)
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+    let signature = builder
+        .sweep_seed_account(&rust_param.seed, &mint)
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+
+    Ok(SeedAccountResult {
+        public_key: public_key.to_string(),
+        signature,
+    })
+}
+
+// realized outcome of a landed transaction for `wallet`, the fee payer when omitted
+#[napi]
+pub async fn get_realized_fill(
+    connection: String,
+    signature: String,
+    wallet: Option<String>,
+) -> Result<RealizedFill4Node, NapiError> {
+
+    let wallet = wallet
+        .map(|wallet| Pubkey::from_str(&wallet))
+        .transpose()
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+    let rpc_client = RpcClient::new(connection);
+
+    fetch_fill(&rpc_client, &signature, wallet.as_ref())
+        .map(|fill| RealizedFill4Node::from(&fill))
+        .map_err(|e| NapiError::from_reason(e.to_string()))
+}
+
+// parse a landed transaction and append it to the position ledger (LEDGER_PATH_RUST),
+// a signature that is already recorded is not written twice
+#[napi]
+pub async fn record_fill(
+    connection: String,
+    signature: String,
+    wallet: Option<String>,
+) -> Result<RealizedFill4Node, NapiError> {
+
+    let wallet = wallet
+        .map(|wallet| Pubkey::from_str(&wallet))
+        .transpose()
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+    let rpc_client = RpcClient::new(connection);
+
+    let fill = fetch_fill(&rpc_client, &signature, wallet.as_ref())
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+    record_ledger_fill(&fill)
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+
+    Ok(RealizedFill4Node::from(&fill))
+}
+
+// realized and unrealized pnl from the ledger, marked against the bonding curve
+#[napi]
+pub async fn get_pnl(
+    connection: String,
+    wallet: Option<String>,
+    mint: Option<String>,
+) -> Result<Vec<WalletPnl4Node>, NapiError> {
+
+    let rpc_client = RpcClient::new(connection);
+
+    get_ledger_pnl(&rpc_client, wallet.as_deref(), mint.as_deref())
+        .map_err(|e| NapiError::from_reason(e.to_string()))
+}
+
+// register a take-profit / stop-loss / trailing / market-cap exit for a position
+#[napi]
+pub fn add_exit_rule(
+    rule: ExitRule4Node,
+) -> Result<u32, NapiError> {
+    let rust_rule: ExitRule = rule.into();
+    exit_rules::add_exit_rule(rust_rule)
+        .map_err(|e| NapiError::from_reason(e.to_string()))
+}
+
+#[napi]
+pub fn remove_exit_rule(
+    rule_id: u32,
+) -> Result<bool, NapiError> {
+    exit_rules::remove_exit_rule(rule_id)
+        .map_err(|e| NapiError::from_reason(e.to_string()))
+}
+
+#[napi]
+pub fn list_exit_rules() -> Vec<ExitRule4Node> {
+    exit_rules::list_exit_rules().iter().map(ExitRule4Node::from).collect()
+}
+
+// evaluate the exit rules of `param`'s wallet on every curve update and sell through `swap`
+// settings when one fires. returns an id for `stop_listener`
+#[napi]
+pub async fn start_exit_watcher(
+    param: SwapParam4Node,
+    ws_url: String,
+    callback: ThreadsafeFunction<ExitTriggerEvent>,
+) -> Result<u32, NapiError> {
+    let rust_param: SwapParam = param.into();
+    rust_param.signer()
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+
+    let callback = Arc::new(callback);
+    Ok(exit_rules::start_exit_watcher(rust_param, ws_url, Arc::new(move |event| {
+        callback.call(Ok(event), ThreadsafeFunctionCallMode::NonBlocking);
+    })))
+}
+
+// close all empty token accounts of the wallet and reclaim their rent.
+// the wallet is the loaded `wallet_id`, else the base58 `secret_key`
+#[napi]
+pub async fn close_empty_token_accounts(
+    connection: String,
+    secret_key: Option<String>,
+    wallet_id: Option<String>,
+) -> Result<CloseTokenAccountsResult, NapiError> {
+
+    let secret_key = Secret::new(secret_key.unwrap_or_default());
+    let fee_payer = resolve_signer(wallet_id.as_deref(), &secret_key)
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+    let builder = TransactionBuilder::new(connection, fee_payer);
+
+    let (closed, signatures) = builder.close_empty_token_accounts()
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+
+    Ok(CloseTokenAccountsResult {
+        closed,
+        signatures,
+    })
+}
+
+// mirror every pump.fun trade of `param.track_token_account`, events are pushed to `callback`.
+// returns an id for `stop_copy_trade`
+#[napi]
+pub async fn start_copy_trade(
+    param: SwapParam4Node,
+    ws_url: String,
+    ratio_bps: Option<u32>,
+    callback: ThreadsafeFunction<CopyTradeEvent>,
+) -> Result<u32, NapiError> {
+
+    let rust_param: SwapParam = param.into();
+    rust_param.signer()
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+    let target = parse_target(&rust_param)
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+
+    let config = CopyTradeConfig {
+        ws_url,
+        target,
+        ratio_bps: ratio_bps.unwrap_or(10_000) as u64,
+        template: rust_param,
+    };
+
+    let callback = Arc::new(callback);
+    Ok(copy_trade::start_copy_trade(config, Arc::new(move |event| {
+        callback.call(Ok(event), ThreadsafeFunctionCallMode::NonBlocking);
+    })))
+}
+
+#[napi]
+pub fn stop_copy_trade(
+    copy_id: u32,
+) -> bool {
+    copy_trade::stop_copy_trade(copy_id)
+}
+
+// stream pump.fun create/trade/complete events from `ws_url` into `callback`.
+// returns an id for `stop_listener`
+#[napi]
+pub async fn start_listener(
+    ws_url: String,
+    callback: ThreadsafeFunction<PumpfunEvent4Node>,
+) -> u32 {
+    let callback = Arc::new(callback);
+    listener::start_listener(ws_url, Arc::new(move |signature, slot, event| {
+        callback.call(
+            Ok(PumpfunEvent4Node::new(signature, slot, event)),
+            ThreadsafeFunctionCallMode::NonBlocking,
+        );
+    }))
+}
+
+// same events as `start_listener` from a yellowstone geyser endpoint, also keeps the
+// bonding curve cache current. only available when built with the `geyser` feature
+#[cfg(feature = "geyser")]
+#[napi]
+pub async fn start_geyser_listener(
+    endpoint: String,
+    x_token: Option<String>,
+    callback: ThreadsafeFunction<PumpfunEvent4Node>,
+) -> u32 {
+    let callback = Arc::new(callback);
+    geyser::start_geyser_listener(endpoint, x_token, Arc::new(move |signature, slot, event| {
+        callback.call(
+            Ok(PumpfunEvent4Node::new(signature, slot, event)),
+            ThreadsafeFunctionCallMode::NonBlocking,
+        );
+    }))
+}
+
+#[napi]
+pub fn stop_listener(
+    listener_id: u32,
+) -> bool {
+    listener::stop_listener(listener_id)
+}
+
+// background keep-alive for the given relays, replaces a running one.
+// failing endpoints are skipped by `swap` until they answer again
+#[napi]
+pub async fn start_keep_alive(
+    targets: Vec<KeepAliveTarget4Node>,
+) -> Result<(), NapiError> {
+    let targets = targets.into_iter()
+        .map(KeepAliveTarget::from_node)
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map_err(|e| NapiError::from_reason(e.to_string()))?;
+    keep_alive::start_keep_alive(targets);
+    Ok(())
+}
+
+#[napi]
+pub fn stop_keep_alive() -> u32 {
+    keep_alive::stop_keep_alive()
+}
+
+#[napi]
+pub fn get_relay_health() -> Vec<RelayHealth4Node> {
+    relay_health_snapshot().iter().map(RelayHealth4Node::from).collect()
+}
+
+// measure every catalogued region of `relays` (all relays when omitted); trades then use the
+// fastest one unless `block_engine_locate` picks a region
+#[napi]
+pub async fn probe_regions(
+    relays: Option<Vec<String>>,
+) -> Result<Vec<RegionProbe4Node>, NapiError> {
+    let relays = parse_relay_names(relays)?;
+
+    Ok(region_probe::probe_regions(&relays).await
+        .iter()
+        .map(RegionProbe4Node::from)
+        .collect())
+}
+
+// relay names from node, every relay when omitted
+fn parse_relay_names(
+    relays: Option<Vec<String>>,
+) -> Result<Vec<u8>, NapiError> {
+    match relays {
+        Some(names) => names.iter()
+            .map(|name| parse_sender_name(name)
+                .ok_or_else(|| NapiError::from_reason(format!("unknown relay {:?}", name))))
+            .collect(),
+        None => Ok(vec![
+            utils::TYPE_JITO,
+            utils::TYPE_BLOXROUTE,
+            utils::TYPE_TEMPORAL,
+            utils::TYPE_NEXTBLOCK,
+            utils::TYPE_0SLOT_TRADE,
+            utils::TYPE_NODE1_ME,
+        ]),
+    }
+}
+
+// rebuild a relay's http client with new timeouts / http2 / local binding / scheme / tls trust
+#[napi]
+pub fn configure_relay_client(
+    config: RelayClientConfig4Node,
+) -> Result<(), NapiError> {
+    let relay = parse_sender_name(&config.relay)
+        .ok_or_else(|| NapiError::from_reason(format!("unknown relay {:?}", config.relay)))?;
+    RelayClientConfig::from_node(relay, &config)
+        .and_then(|rust_config| http_clients::configure_relay_client(relay, &rust_config))
+        .map_err(|e| NapiError::from_reason(e.to_string()))
+}
+
+// open connections to the endpoints trades would use and report the connection setup cost.
+// call at startup, and with `idleMs` to only rewarm relays that have been quiet that long
+#[napi]
+pub async fn prewarm_relays(
+    relays: Option<Vec<String>>,
+    idle_ms: Option<u32>,
+    region_fan_out: Option<u32>,
+) -> Result<Vec<PrewarmResult>, NapiError> {
+    let relays = parse_relay_names(relays)?;
+    let idle = idle_ms.map(|ms| std::time::Duration::from_millis(ms as u64));
+    let region_count = region_fan_out.unwrap_or(1).max(1) as usize;
+
+    Ok(http_clients::prewarm_relays(&relays, idle, region_count).await)
+}
+
 #[napi]
 pub async fn keepalive_bloxroute(
     api_key: String,
@@ -60,6 +496,14 @@ pub async fn keepalive_nodeme(
     let _ = send_nodeme(&api_url).await;
 }
 
+#[napi]
+pub async fn keepalive_nextblock(
+    api_url: String,
+    auth_key: String,
+){
+    let _ = send_nextblock(&api_url, &auth_key).await;
+}
+
 #[napi]
 pub async fn keepalive_temporal(
     api_url: String,
@@ -73,4 +517,70 @@ pub async fn keepalive_slot(
     auth_key: String,
 ){
     let _ = send_0slot(&api_url, &auth_key).await;
-}
\ No newline at end of file
+}
+
+// tracing filter for the addon's logs, a level ("debug") or a filter ("info,hyper=warn")
+#[napi]
+pub fn set_log_level(
+    level: String,
+) -> Result<(), NapiError> {
+    telemetry::set_log_level(&level).map_err(|e| NapiError::from_reason(e.to_string()))
+}
+
+// trade stage and relay latency histograms plus send counters, prometheus text format
+#[napi]
+pub fn get_metrics() -> String {
+    metrics::render_prometheus()
+}
+
+#[napi]
+pub fn reset_metrics() {
+    metrics::reset_metrics()
+}
+
+// load a solana cli keypair file, or an encrypted keystore with `password`, under `label`.
+// trades then pass `walletId: label` instead of `secretKey`. returns the public key
+#[napi]
+pub fn load_wallet(
+    label: String,
+    path: String,
+    password: Option<String>,
+) -> Result<String, NapiError> {
+    let password = password.map(Secret::new);
+    keystore::load_wallet(&label, &path, password.as_ref())
+        .map(|pubkey| pubkey.to_string())
+        .map_err(|e| NapiError::from_reason(e.to_string()))
+}
+
+#[napi]
+pub fn import_wallet(
+    label: String,
+    secret_key: String,
+) -> Result<String, NapiError> {
+    keystore::import_wallet(&label, &Secret::new(secret_key))
+        .map(|pubkey| pubkey.to_string())
+        .map_err(|e| NapiError::from_reason(e.to_string()))
+}
+
+// encrypt a loaded wallet into a keystore file that `load_wallet` reads back with the password
+#[napi]
+pub fn save_encrypted_wallet(
+    label: String,
+    path: String,
+    password: String,
+) -> Result<(), NapiError> {
+    keystore::save_encrypted_wallet(&label, &path, &Secret::new(password))
+        .map_err(|e| NapiError::from_reason(e.to_string()))
+}
+
+#[napi]
+pub fn unload_wallet(
+    label: String,
+) -> bool {
+    keystore::unload_wallet(&label)
+}
+
+#[napi]
+pub fn list_wallets() -> Vec<WalletInfo> {
+    keystore::list_wallets()
+}
@@ -0,0 +1,309 @@
+use anyhow::{anyhow, Result};
+use futures::StreamExt;
+use solana_client::nonblocking::pubsub_client::PubsubClient;
+use solana_client::rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use solana_sdk::commitment_config::CommitmentConfig;
+use napi_derive::napi;
+use tokio::task::JoinHandle;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tracing::warn;
+
+use crate::configs::global::PUMPFUN_PROGRAM_ID;
+use crate::events::{decode_pumpfun_events, PumpfunEvent};
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+// flat view of a pump.fun event for node, fields that do not apply to `kind` are empty
+#[derive(Debug, Clone, Default)]
+#[napi(object)]
+pub struct PumpfunEvent4Node {
+    pub kind: String, // "create" | "trade" | "complete"
+    pub signature: String,
+    pub slot: i64,
+    pub mint: String,
+    pub user: String,
+    pub timestamp: i64,
+    pub bonding_curve: Option<String>,
+    pub creator: Option<String>,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub uri: Option<String>,
+    pub is_buy: Option<bool>,
+    pub sol_amount: Option<String>,
+    pub token_amount: Option<String>,
+    pub virtual_sol_reserves: Option<String>,
+    pub virtual_token_reserves: Option<String>,
+}
+
+impl PumpfunEvent4Node {
+
+    pub fn new(
+        signature: &str,
+        slot: u64,
+        event: &PumpfunEvent,
+    ) -> Self {
+        let mut node = PumpfunEvent4Node {
+            signature: signature.to_string(),
+            slot: slot as i64,
+            mint: event.mint().to_string(),
+            ..PumpfunEvent4Node::default()
+        };
+
+        match event {
+            PumpfunEvent::Create(event) => {
+                node.kind = "create".to_string();
+                node.user = event.user.to_string();
+                node.timestamp = event.timestamp;
+                node.bonding_curve = Some(event.bonding_curve.to_string());
+                node.creator = Some(event.creator.to_string());
+                node.name = Some(event.name.clone());
+                node.symbol = Some(event.symbol.clone());
+                node.uri = Some(event.uri.clone());
+                node.virtual_sol_reserves = Some(event.virtual_sol_reserves.to_string());
+                node.virtual_token_reserves = Some(event.virtual_token_reserves.to_string());
+            },
+            PumpfunEvent::Trade(event) => {
+                node.kind = "trade".to_string();
+                node.user = event.user.to_string();
+                node.timestamp = event.timestamp;
+                node.is_buy = Some(event.is_buy);
+                node.sol_amount = Some(event.sol_amount.to_string());
+                node.token_amount = Some(event.token_amount.to_string());
+                node.virtual_sol_reserves = Some(event.virtual_sol_reserves.to_string());
+                node.virtual_token_reserves = Some(event.virtual_token_reserves.to_string());
+            },
+            PumpfunEvent::Complete(event) => {
+                node.kind = "complete".to_string();
+                node.user = event.user.to_string();
+                node.timestamp = event.timestamp;
+                node.bonding_curve = Some(event.bonding_curve.to_string());
+            },
+        }
+
+        node
+    }
+}
+
+pub type EventCallback = Arc<dyn Fn(&str, u64, &PumpfunEvent) + Send + Sync>;
+
+static LISTENERS: OnceLock<Mutex<HashMap<u32, JoinHandle<()>>>> = OnceLock::new();
+static NEXT_LISTENER_ID: AtomicU32 = AtomicU32::new(1);
+
+fn listeners() -> &'static Mutex<HashMap<u32, JoinHandle<()>>> {
+    LISTENERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// run a subscription in the background, restarting it whenever it fails. `run` gets the
+// listener id. stream sources share the registry so `stop_listener` stops any of them
+pub(crate) fn spawn_listener<F, Fut>(
+    run: F,
+) -> u32
+where
+    F: Fn(u32) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    let listener_id = NEXT_LISTENER_ID.fetch_add(1, Ordering::Relaxed);
+    let handle = tokio::spawn(async move {
+        loop {
+            if let Err(e) = run(listener_id).await {
+                warn!(listener_id, "subscription failed: {}", e);
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+    listeners().lock().unwrap().insert(listener_id, handle);
+    listener_id
+}
+
+// subscribe to pump.fun program logs on `ws_url` and hand every decoded event to `callback`.
+// the subscription is re-established until `stop_listener` is called
+pub fn start_listener(
+    ws_url: String,
+    callback: EventCallback,
+) -> u32 {
+    spawn_listener(move |_| {
+        let ws_url = ws_url.clone();
+        let callback = callback.clone();
+        async move { run_listener(&ws_url, &callback).await }
+    })
+}
+
+pub fn stop_listener(listener_id: u32) -> bool {
+    match listeners().lock().unwrap().remove(&listener_id) {
+        Some(handle) => {
+            handle.abort();
+            true
+        },
+        None => false,
+    }
+}
+
+async fn run_listener(
+    ws_url: &str,
+    callback: &EventCallback,
+) -> Result<()> {
+    let client = PubsubClient::new(ws_url).await?;
+    let (mut stream, _unsubscribe) = client.logs_subscribe(
+        RpcTransactionLogsFilter::Mentions(vec![PUMPFUN_PROGRAM_ID.to_string()]),
+        RpcTransactionLogsConfig {
+            commitment: Some(CommitmentConfig::processed()),
+        },
+    ).await?;
+
+    while let Some(response) = stream.next().await {
+        let logs = response.value;
+        if logs.err.is_some() {
+            continue;
+        }
+
+        for event in decode_pumpfun_events(&logs.logs) {
+            callback(&logs.signature, response.context.slot, &event);
+        }
+    }
+
+    Err(anyhow!("log subscription closed"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{CompleteEvent, CreateEvent, TradeEvent};
+    use crate::utils::sighash;
+    use base64::{Engine as _, engine::general_purpose};
+    use borsh::BorshSerialize;
+    use futures::SinkExt;
+    use serde_json::{json, Value};
+    use solana_program::pubkey::Pubkey;
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::Message;
+
+    const SUBSCRIPTION: u64 = 7;
+
+    fn program_data<T: BorshSerialize>(name: &str, event: &T) -> String {
+        let mut data = sighash("event", name).to_vec();
+        event.serialize(&mut data).unwrap();
+        format!("Program data: {}", general_purpose::STANDARD.encode(data))
+    }
+
+    fn logs_notification(slot: u64, signature: &str, err: Value, logs: Vec<String>) -> String {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "logsNotification",
+            "params": {
+                "result": {
+                    "context": { "slot": slot },
+                    "value": { "signature": signature, "err": err, "logs": logs },
+                },
+                "subscription": SUBSCRIPTION,
+            },
+        }).to_string()
+    }
+
+    // stands in for the rpc websocket: acks the logsSubscribe request, replays `frames`
+    // and keeps the connection open
+    async fn replay_server(frames: Vec<String>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let request = loop {
+                match ws.next().await.unwrap().unwrap() {
+                    Message::Text(text) => break serde_json::from_str::<Value>(&text).unwrap(),
+                    _ => continue,
+                }
+            };
+            assert_eq!(request["method"], "logsSubscribe");
+            assert_eq!(request["params"][0]["mentions"][0], PUMPFUN_PROGRAM_ID);
+            let ack = json!({ "jsonrpc": "2.0", "result": SUBSCRIPTION, "id": request["id"] });
+            ws.send(Message::Text(ack.to_string())).await.unwrap();
+            for frame in frames {
+                ws.send(Message::Text(frame)).await.unwrap();
+            }
+            while ws.next().await.is_some() {}
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn replays_logs_notifications_into_events() {
+        let (mint, user, bonding_curve) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let create = CreateEvent {
+            name: "Coin".to_string(),
+            symbol: "COIN".to_string(),
+            uri: "https://ipfs.io/ipfs/x".to_string(),
+            mint,
+            bonding_curve,
+            user,
+            creator: user,
+            timestamp: 1_700_000_000,
+            virtual_token_reserves: 1_073_000_000_000_000,
+            virtual_sol_reserves: 30_000_000_000,
+            real_token_reserves: 793_100_000_000_000,
+            token_total_supply: 1_000_000_000_000_000,
+        };
+        let trade = TradeEvent {
+            mint,
+            sol_amount: 1_000_000_000,
+            token_amount: 34_612_903_225_806,
+            is_buy: true,
+            user,
+            timestamp: 1_700_000_001,
+            virtual_sol_reserves: 31_000_000_000,
+            virtual_token_reserves: 1_038_387_096_774_194,
+        };
+        let complete = CompleteEvent { user, mint, bonding_curve, timestamp: 1_700_000_002 };
+
+        let frames = vec![
+            logs_notification(100, "create-sig", Value::Null, vec![
+                "Program 6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P invoke [1]".to_string(),
+                "Program log: Instruction: Create".to_string(),
+                program_data("CreateEvent", &create),
+                "Program 6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P success".to_string(),
+            ]),
+            // failed transactions are skipped
+            logs_notification(101, "failed-sig", json!({ "InstructionError": [0, { "Custom": 6002 }] }), vec![
+                program_data("TradeEvent", &trade),
+            ]),
+            logs_notification(102, "trade-sig", Value::Null, vec![
+                "Program log: Instruction: Buy".to_string(),
+                program_data("TradeEvent", &trade),
+                program_data("CompleteEvent", &complete),
+            ]),
+        ];
+        let ws_url = replay_server(frames).await;
+
+        let received = Arc::new(Mutex::new(vec![]));
+        let sink = received.clone();
+        let listener_id = start_listener(ws_url, Arc::new(move |signature, slot, event| {
+            sink.lock().unwrap().push(PumpfunEvent4Node::new(signature, slot, event));
+        }));
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+        while received.lock().unwrap().len() < 3 && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(stop_listener(listener_id));
+
+        let events = received.lock().unwrap().clone();
+        let kinds: Vec<(&str, &str, i64)> = events.iter()
+            .map(|e| (e.kind.as_str(), e.signature.as_str(), e.slot))
+            .collect();
+        assert_eq!(kinds, vec![("create", "create-sig", 100), ("trade", "trade-sig", 102), ("complete", "trade-sig", 102)]);
+
+        assert_eq!(events[0].mint, mint.to_string());
+        assert_eq!(events[0].symbol.as_deref(), Some("COIN"));
+        assert_eq!(events[0].bonding_curve, Some(bonding_curve.to_string()));
+        assert_eq!(events[1].is_buy, Some(true));
+        assert_eq!(events[1].sol_amount.as_deref(), Some("1000000000"));
+        assert_eq!(events[1].token_amount.as_deref(), Some("34612903225806"));
+        assert_eq!(events[1].virtual_sol_reserves.as_deref(), Some("31000000000"));
+        assert_eq!(events[2].user, user.to_string());
+        assert_eq!(events[2].timestamp, 1_700_000_002);
+    }
+}
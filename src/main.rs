@@ -2,6 +2,14 @@ mod request;
 mod contracts;
 mod configs;
 mod utils;
+mod secret;
+mod http_clients;
+mod keep_alive;
+mod region_probe;
+mod tls;
+mod metrics;
+mod latency;
+mod keystore;
 
 use solana_sdk::{
     pubkey::Pubkey,
@@ -47,6 +55,7 @@ use anyhow::{anyhow};
 
 use serde::Deserialize;
 use crate::utils::{SwapParam, build_tip_transfer_instruction, SolAccountStruct};
+use crate::secret::Secret;
 
 use anchor_lang::Discriminator;
 use anchor_spl::token::accessor::mint;
@@ -558,7 +567,7 @@ async fn main() -> Result<()> {
     let param = SwapParam {
         connection: "".to_string(),
         connection_brand : "".to_string(),
-        secret_key : private_key.to_string(),
+        secret_key : Secret::new(private_key.to_string()),
         amount_in : amount,
         amount_out : 1000,
         fixed_side : "in".to_string(),
@@ -645,6 +654,13 @@ async fn main() -> Result<()> {
         anti_mev: None, // third-party api prevents MEV, 0 no, 1 yes
 
         creator_vault: None,
+
+        pumpfun_direct: None,
+
+        sell_mode: None,
+
+        region_fan_out: None,
+        wallet_id: None,
     };
 
     let response = builder.trade(&param).await;
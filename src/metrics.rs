@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+// in-process latency histograms and counters, rendered in the prometheus text format
+// by `render_prometheus`. labels are kept as (name, value) pairs sorted by series
+
+// seconds, relay round trips sit between a few ms and a couple of seconds
+const LATENCY_BUCKETS: &[f64] = &[0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    buckets: Vec<u64>, // cumulative count per LATENCY_BUCKETS entry
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+
+    fn observe(&mut self, seconds: f64) {
+        if self.buckets.is_empty() {
+            self.buckets = vec![0; LATENCY_BUCKETS.len()];
+        }
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= *bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+type Labels = Vec<(&'static str, String)>;
+
+#[derive(Debug, Default)]
+struct Registry {
+    histograms: BTreeMap<(&'static str, Labels), Histogram>,
+    counters: BTreeMap<(&'static str, Labels), u64>,
+}
+
+static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+
+fn registry() -> &'static Mutex<Registry> {
+    REGISTRY.get_or_init(|| Mutex::new(Registry::default()))
+}
+
+fn help(name: &str) -> &'static str {
+    match name {
+        "pumpfun_trade_stage_seconds" => "Time spent in each stage of a trade",
+        "pumpfun_relay_send_seconds" => "Round trip of a transaction submission per relay and outcome",
+        "pumpfun_relay_sends_total" => "Transaction submissions per relay and outcome",
+        _ => "",
+    }
+}
+
+pub fn observe_histogram(name: &'static str, labels: Labels, elapsed: Duration) {
+    registry().lock().unwrap()
+        .histograms
+        .entry((name, labels))
+        .or_default()
+        .observe(elapsed.as_secs_f64());
+}
+
+pub fn increment_counter(name: &'static str, labels: Labels) {
+    *registry().lock().unwrap()
+        .counters
+        .entry((name, labels))
+        .or_default() += 1;
+}
+
+// stage is one of build / blockhash / sign / send / total
+pub fn observe_trade_stage(stage: &str, elapsed: Duration) {
+    observe_histogram("pumpfun_trade_stage_seconds", vec![("stage", stage.to_string())], elapsed);
+}
+
+pub fn observe_relay_send(relay: &str, ok: bool, elapsed: Duration) {
+    let labels = vec![
+        ("relay", relay.to_string()),
+        ("outcome", if ok { "ok" } else { "error" }.to_string()),
+    ];
+    observe_histogram("pumpfun_relay_send_seconds", labels.clone(), elapsed);
+    increment_counter("pumpfun_relay_sends_total", labels);
+}
+
+fn format_labels(labels: &[(&'static str, String)], extra: Option<(&str, String)>) -> String {
+    let mut pairs: Vec<String> = labels.iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, value.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect();
+    if let Some((name, value)) = extra {
+        pairs.push(format!("{}=\"{}\"", name, value));
+    }
+    if pairs.is_empty() { String::new() } else { format!("{{{}}}", pairs.join(",")) }
+}
+
+pub fn render_prometheus() -> String {
+    let registry = registry().lock().unwrap();
+    let mut out = String::new();
+
+    let mut last_name = "";
+    for ((name, labels), histogram) in &registry.histograms {
+        if *name != last_name {
+            let _ = writeln!(out, "# HELP {} {}", name, help(name));
+            let _ = writeln!(out, "# TYPE {} histogram", name);
+            last_name = name;
+        }
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(&histogram.buckets) {
+            let _ = writeln!(out, "{}_bucket{} {}", name, format_labels(labels, Some(("le", bound.to_string()))), count);
+        }
+        let _ = writeln!(out, "{}_bucket{} {}", name, format_labels(labels, Some(("le", "+Inf".to_string()))), histogram.count);
+        let _ = writeln!(out, "{}_sum{} {}", name, format_labels(labels, None), histogram.sum);
+        let _ = writeln!(out, "{}_count{} {}", name, format_labels(labels, None), histogram.count);
+    }
+
+    let mut last_name = "";
+    for ((name, labels), value) in &registry.counters {
+        if *name != last_name {
+            let _ = writeln!(out, "# HELP {} {}", name, help(name));
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            last_name = name;
+        }
+        let _ = writeln!(out, "{}{} {}", name, format_labels(labels, None), value);
+    }
+    out
+}
+
+pub fn reset_metrics() {
+    let mut registry = registry().lock().unwrap();
+    registry.histograms.clear();
+    registry.counters.clear();
+}
@@ -1,6 +1,8 @@
 use solana_program::native_token::LAMPORTS_PER_SOL;
 // src/conversion.rs
 use crate::utils::*;
+use crate::secret::Secret;
+use crate::exit_rules::{ExitRule, ExitRule4Node, RULE_STATUS_ACTIVE};
 
 // impl From<SolAccountStructJs> for SolAccountStruct {
 //     fn from(js: SolAccountStructJs) -> Self {
@@ -16,7 +18,7 @@ impl From<SwapParam4Node> for SwapParam {
         SwapParam {
             connection: node.connection,
             connection_brand : node.connection_brand,
-            secret_key: node.secret_key,
+            secret_key: Secret::new(node.secret_key.unwrap_or_default()),
             amount_in: node.amount_in.parse().unwrap(),
             amount_out: node.amount_out.parse().unwrap(),
             fixed_side: node.fixed_side,
@@ -97,6 +99,80 @@ impl From<SwapParam4Node> for SwapParam {
 
             creator_vault: node.creator_vault,
 
+            pumpfun_direct: node.pumpfun_direct,
+
+            sell_mode: node.sell_mode,
+
+            region_fan_out: node.region_fan_out.map(|s| s.parse().unwrap()),
+            wallet_id: node.wallet_id,
+
+        }
+    }
+}
+
+impl From<BundleBuy4Node> for BundleBuy {
+    fn from(node: BundleBuy4Node) -> Self {
+        BundleBuy {
+            secret_key: Secret::new(node.secret_key.unwrap_or_default()),
+            amount_in: node.amount_in.parse().unwrap(),
+            wallet_id: node.wallet_id,
+        }
+    }
+}
+
+impl From<LaunchParam4Node> for LaunchParam {
+    fn from(node: LaunchParam4Node) -> Self {
+        LaunchParam {
+            swap: node.swap.into(),
+            name: node.name,
+            symbol: node.symbol,
+            uri: node.uri,
+            mint_secret_key: node.mint_secret_key.map(Secret::new),
+            bundle_buys: node.bundle_buys
+                .unwrap_or_default()
+                .into_iter()
+                .map(|b| b.into())
+                .collect(),
+        }
+    }
+}
+
+impl From<BundleBuyParam4Node> for BundleBuyParam {
+    fn from(node: BundleBuyParam4Node) -> Self {
+        BundleBuyParam {
+            swap: node.swap.into(),
+            wallets: node.wallets.into_iter().map(|b| b.into()).collect(),
+        }
+    }
+}
+
+impl From<SeedAccountParam4Node> for SeedAccountParam {
+    fn from(node: SeedAccountParam4Node) -> Self {
+        SeedAccountParam {
+            connection: node.connection,
+            secret_key: Secret::new(node.secret_key.unwrap_or_default()),
+            seed: node.seed,
+            mint: node.mint,
+            lamports: node.lamports.map(|s| s.parse().unwrap()).unwrap_or(0),
+            wallet_id: node.wallet_id,
+        }
+    }
+}
+
+impl From<ExitRule4Node> for ExitRule {
+    fn from(node: ExitRule4Node) -> Self {
+        ExitRule {
+            id: node.id.unwrap_or(0),
+            wallet: node.wallet,
+            mint: node.mint,
+            entry_sol: node.entry_sol.parse().unwrap(),
+            token_amount: node.token_amount.parse().unwrap(),
+            take_profit_bps: node.take_profit_bps.map(|bps| bps as u64),
+            stop_loss_bps: node.stop_loss_bps.map(|bps| bps as u64),
+            trailing_stop_bps: node.trailing_stop_bps.map(|bps| bps as u64),
+            market_cap_sol: node.market_cap_sol.map(|s| s.parse().unwrap()),
+            peak_value: node.peak_value.map(|s| s.parse().unwrap()).unwrap_or(0),
+            status: node.status.unwrap_or_else(|| RULE_STATUS_ACTIVE.to_string()),
         }
     }
-}
\ No newline at end of file
+}
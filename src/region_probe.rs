@@ -0,0 +1,127 @@
+use futures::future::join_all;
+use napi_derive::napi;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use std::time::Instant;
+use tracing::warn;
+
+use crate::configs::bribe::get_sender_name;
+use crate::configs::regions::{get_region_host, get_relay_regions, parse_block_engine_locate};
+use crate::keep_alive::{get_relay_env_url, get_relay_key, is_relay_healthy, ping_relay, record_ping};
+
+// rtt of every catalogued region per relay, filled by `probe_regions`
+
+#[derive(Debug, Clone)]
+pub struct RegionProbe {
+    pub relay: u8,
+    pub region: String,
+    pub host: String,
+    pub rtt_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct RegionProbe4Node {
+    pub relay: String,
+    pub region: String,
+    pub host: String,
+    pub rtt_ms: Option<u32>,
+    pub error: Option<String>,
+}
+
+impl From<&RegionProbe> for RegionProbe4Node {
+    fn from(probe: &RegionProbe) -> Self {
+        RegionProbe4Node {
+            relay: get_sender_name(probe.relay).to_string(),
+            region: probe.region.clone(),
+            host: probe.host.clone(),
+            rtt_ms: probe.rtt_ms.map(|rtt| rtt as u32),
+            error: probe.error.clone(),
+        }
+    }
+}
+
+static REGION_PROBES: OnceLock<RwLock<HashMap<u8, Vec<RegionProbe>>>> = OnceLock::new();
+
+fn region_probes() -> &'static RwLock<HashMap<u8, Vec<RegionProbe>>> {
+    REGION_PROBES.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+// ping every region of the relays concurrently, results are sorted fastest first
+pub async fn probe_regions(relays: &[u8]) -> Vec<RegionProbe> {
+    let probes = relays.iter().flat_map(|relay| {
+        get_relay_regions(*relay).iter().map(move |(region, host)| async move {
+            let start = Instant::now();
+            let result = ping_relay(*relay, host, get_relay_key(*relay)).await;
+            let rtt = start.elapsed();
+            record_ping(*relay, host, &result, rtt);
+
+            let (rtt_ms, error) = match &result {
+                Ok(status) if *status < 500 => (Some(rtt.as_millis() as u64), None),
+                Ok(status) => (None, Some(format!("status {}", status))),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            RegionProbe {
+                relay: *relay,
+                region: region.to_string(),
+                host: host.to_string(),
+                rtt_ms,
+                error,
+            }
+        })
+    });
+
+    let mut results = join_all(probes).await;
+    results.sort_by_key(|probe| (probe.relay, probe.rtt_ms.unwrap_or(u64::MAX)));
+
+    let mut by_relay = region_probes().write().unwrap();
+    for relay in relays {
+        by_relay.insert(*relay, results.iter().filter(|probe| probe.relay == *relay).cloned().collect());
+    }
+    results
+}
+
+// up to `count` reachable hosts of the relay, fastest first
+pub fn fastest_regions(relay: u8, count: usize) -> Vec<String> {
+    region_probes().read().unwrap()
+        .get(&relay)
+        .map(|probes| probes.iter()
+            .filter(|probe| probe.rtt_ms.is_some() && is_relay_healthy(&probe.host))
+            .take(count)
+            .map(|probe| probe.host.clone())
+            .collect())
+        .unwrap_or_default()
+}
+
+// endpoints a trade uses for the relay: the regions asked for in `block_engine_locate`,
+// else the `count` fastest probed regions, else the relay's env var (comma separated)
+pub fn resolve_relay_urls(relay: u8, block_engine_locate: Option<&str>, count: usize) -> Vec<String> {
+    let regions = block_engine_locate
+        .map(|locate| parse_block_engine_locate(locate, relay))
+        .unwrap_or_default();
+    let hosts: Vec<String> = regions.iter()
+        .filter_map(|region| match get_region_host(relay, region) {
+            Some(host) => Some(host.to_string()),
+            None => {
+                warn!("no {} region {:?}, skipped", get_sender_name(relay), region);
+                None
+            }
+        })
+        .collect();
+    if !hosts.is_empty() {
+        return hosts;
+    }
+
+    let fastest = fastest_regions(relay, count);
+    if !fastest.is_empty() {
+        return fastest;
+    }
+
+    get_relay_env_url(relay)
+        .map(|urls| urls.split(',')
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty())
+            .collect())
+        .unwrap_or_default()
+}
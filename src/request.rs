@@ -1,7 +1,6 @@
 // src/request.rs
 use anyhow::{anyhow, Result, Context};
 use reqwest::{
-    Client,
     header::{HeaderMap, HeaderValue, CONTENT_TYPE, AUTHORIZATION},
 };
 use solana_sdk::{
@@ -13,7 +12,10 @@ use base64::{Engine as _, engine::general_purpose};
 use serde_json::json;
 use std::sync::OnceLock;
 use std::time::Duration;
-use crate::utils::get_client;
+use crate::configs::bribe::*;
+use crate::http_clients::{get_relay_client, relay_request};
+use crate::secret::scrub_reqwest_error;
+use tracing::debug;
 
 #[derive(Debug, Serialize)]
 pub struct SignedTransaction {
@@ -45,8 +47,8 @@ struct SubmitResponse {
 }
 
 
+// requests go through the relay's own client, see `http_clients`
 pub struct ThirdPartySender {
-    client: &'static Client,
     // api_url: String,
     // auth_key: String,
 }
@@ -57,9 +59,7 @@ impl ThirdPartySender {
     pub fn new(
         // api_url: String, auth_key: String
     ) -> Result<Self> {
-        let client = get_client();
         Ok(ThirdPartySender {
-            client,
             // api_url,
             // auth_key,
         })
@@ -147,24 +147,18 @@ impl ThirdPartySender {
                 ],
               });
 
-        let mut headers = HeaderMap::new();
-        headers.insert("Content-Type", HeaderValue::from_static("application/json"));
-        headers.insert("api-key", HeaderValue::from_str(&auth_key)?);
-        // headers.insert("x-sdk", HeaderValue::from_static("rust-client"));
-        // headers.insert(
-        //     "x-sdk-version",
-        //     HeaderValue::from_static(env!("CARGO_PKG_VERSION")),
-        // );
+        let (url, headers) = relay_request(SENDER_NODE1_ME, api_url, "", auth_key)?;
 
-        let response = self.client
-            .post(format!("http://{}", api_url))
+        let response = get_relay_client(SENDER_NODE1_ME)?
+            .post(url)
             .headers(headers)
             .json(&params)
             .send()
             .await
+            .map_err(scrub_reqwest_error)
             .context("Failed to send request")?;
 
-        let response_text = response.text().await?;
+        let response_text = response.text().await.map_err(scrub_reqwest_error)?;
         Ok(response_text)
 
     }
@@ -191,19 +185,18 @@ impl ThirdPartySender {
             ]
         });
 
-        let mut headers = HeaderMap::new();
-        headers.insert("Content-Type", HeaderValue::from_static("application/json"));
-
+        let (url, headers) = relay_request(SENDER_SLOT0_TRADE, api_url, "", auth_key)?;
 
-        let response = self.client
-            .post(format!("http://{}?api-key={}", api_url, auth_key))
+        let response = get_relay_client(SENDER_SLOT0_TRADE)?
+            .post(url)
             .headers(headers)
             .json(&params)
             .send()
             .await
+            .map_err(scrub_reqwest_error)
             .context("Failed to send request")?;
 
-        let response_text = response.text().await?;
+        let response_text = response.text().await.map_err(scrub_reqwest_error)?;
         Ok(response_text)
     }
 
@@ -230,18 +223,18 @@ impl ThirdPartySender {
                 ]
               });
 
-        let mut headers = HeaderMap::new();
-        headers.insert("Content-Type", HeaderValue::from_static("application/json"));
+        let (url, headers) = relay_request(SENDER_TEMPORAL, api_url, "", auth_key)?;
 
-        let response = self.client
-            .post(format!("http://{}?c={}", api_url, auth_key))
+        let response = get_relay_client(SENDER_TEMPORAL)?
+            .post(url)
             .headers(headers)
             .json(&params)
             .send()
             .await
+            .map_err(scrub_reqwest_error)
             .context("Failed to send request")?;
 
-        let response_text = response.text().await?;
+        let response_text = response.text().await.map_err(scrub_reqwest_error)?;
         Ok(response_text)
     }
 
@@ -266,29 +259,66 @@ impl ThirdPartySender {
           ]
         });
 
-        let mut headers = HeaderMap::new();
-        headers.insert("content-type", HeaderValue::from_static("application/json"));
-        headers.insert("x-jito-auth", HeaderValue::from_str(&auth_key)?);
+        let (url, headers) = relay_request(SENDER_JITO, api_url, "/api/v1/bundles", auth_key)?;
 
-        let response = self.client
-            // .post(format!("{}/api/v1/transactions?bundleOnly=true", api_url))
-            .post(format!("https://{}/api/v1/bundles", api_url))
+        let response = get_relay_client(SENDER_JITO)?
+            .post(url)
             .headers(headers)
             .json(&params)
             .send()
             .await
+            .map_err(scrub_reqwest_error)
             .context("Failed to send request")?;
 
-        let response_text = response.text().await?;
+        let response_text = response.text().await.map_err(scrub_reqwest_error)?;
         Ok(response_text)
     }
 
 
-    pub async fn send_nextblock(
+    pub async fn send_jito_bundle(
         &self,
-        transaction: &VersionedTransaction,
+        transactions: &[VersionedTransaction],
         api_url: &str,
         auth_key: &str,
+    ) -> Result<String> {
+        let mut contents = vec![];
+        for transaction in transactions {
+            contents.push(self.pre_handle_transaction(transaction)?);
+        }
+
+        let params = json!({
+          "id": 1,
+          "jsonrpc": "2.0",
+          "method": "sendBundle",
+          "params": [
+            contents,
+            {
+              "encoding": "base64"
+            }
+          ]
+        });
+
+        let (url, headers) = relay_request(SENDER_JITO, api_url, "/api/v1/bundles", auth_key)?;
+
+        let response = get_relay_client(SENDER_JITO)?
+            .post(url)
+            .headers(headers)
+            .json(&params)
+            .send()
+            .await
+            .map_err(scrub_reqwest_error)
+            .context("Failed to send request")?;
+
+        let response_text = response.text().await.map_err(scrub_reqwest_error)?;
+        Ok(response_text)
+    }
+
+
+    pub async fn send_nextblock(
+        &self,
+        _transaction: &VersionedTransaction,
+        _api_url: &str,
+        _auth_key: &str,
     ) -> Result<String> {
         //https://
         Ok("nextbock".to_string()
@@ -310,21 +340,20 @@ impl ThirdPartySender {
             "useStakedRPCs": true,
         });
 
-        let mut headers = HeaderMap::new();
-        headers.insert("Content-Type", HeaderValue::from_static("application/json"));
-        headers.insert("Authorization", HeaderValue::from_str(&auth_key)?);
+        let (url, headers) = relay_request(SENDER_BLOXROUTE, api_url, "/api/v2/submit", auth_key)?;
 
-        let response = self.client
-            .post(format!("http://{}/api/v2/submit", api_url))
+        let response = get_relay_client(SENDER_BLOXROUTE)?
+            .post(url)
             .headers(headers)
             .json(&params)
             .send()
             .await
+            .map_err(scrub_reqwest_error)
             .context("Failed to send request")?;
 
-        println!("response {:?}", response);
+        debug!(status = %response.status(), "bloxroute submit");
 
-        let response_text = response.text().await?;
+        let response_text = response.text().await.map_err(scrub_reqwest_error)?;
         Ok(response_text)
     }
 
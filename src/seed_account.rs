@@ -0,0 +1,129 @@
+use anyhow::{bail, Result};
+use solana_program::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    system_instruction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address,
+    instruction::create_associated_token_account_idempotent,
+};
+use std::str::FromStr;
+use spl_token::native_mint::ID as WSOL_MINT;
+use crate::configs::global::TOKEN_PROGRAM_ID;
+use crate::utils::SolAccountStruct;
+
+// sub-accounts are spl token accounts created with `create_account_with_seed` off the
+// main wallet and owned by it, so one signer can hold many isolated positions
+
+const TOKEN_ACCOUNT_SIZE: u64 = 165;
+const TOKEN_ACCOUNT_RENT: u64 = 2_039_280;
+const MAX_SEED_LEN: usize = 32;
+
+pub fn derive_seed_account(
+    base: &Pubkey,
+    seed: &str,
+) -> Result<Pubkey> {
+    if seed.is_empty() || seed.len() > MAX_SEED_LEN {
+        bail!("seed must be 1 to {} bytes, got {}", MAX_SEED_LEN, seed.len());
+    }
+    Ok(Pubkey::create_with_seed(base, seed, &Pubkey::from_str(TOKEN_PROGRAM_ID)?)?)
+}
+
+// derive the account for `SwapParam.sol_account` and check it against the address JS sent
+pub fn resolve_seed_account(
+    base: &Pubkey,
+    sol_account: &SolAccountStruct,
+) -> Result<Pubkey> {
+    let derived = derive_seed_account(base, &sol_account.seed)?;
+    if !sol_account.public_key.is_empty() && Pubkey::from_str(&sol_account.public_key)? != derived {
+        bail!(
+            "sol_account {} does not match seed {:?} of {}",
+            sol_account.public_key,
+            sol_account.seed,
+            base
+        );
+    }
+    Ok(derived)
+}
+
+// create the sub-account for `mint`, a native mint account is funded with `lamports` of wsol
+pub fn create_seed_account_instructions(
+    base: &Pubkey,
+    seed: &str,
+    mint: &Pubkey,
+    lamports: u64,
+) -> Result<Vec<Instruction>> {
+    let token_program_id = Pubkey::from_str(TOKEN_PROGRAM_ID)?;
+    let seed_account = derive_seed_account(base, seed)?;
+
+    let mut instructions = vec![
+        system_instruction::create_account_with_seed(
+            base,
+            &seed_account,
+            base,
+            seed,
+            TOKEN_ACCOUNT_RENT,
+            TOKEN_ACCOUNT_SIZE,
+            &token_program_id,
+        ),
+        spl_token::instruction::initialize_account3(
+            &token_program_id,
+            &seed_account,
+            mint,
+            base,
+        )?,
+    ];
+
+    if lamports > 0 {
+        if *mint != WSOL_MINT {
+            bail!("only native mint sub-accounts can be funded with sol");
+        }
+        instructions.push(system_instruction::transfer(base, &seed_account, lamports));
+        instructions.push(spl_token::instruction::sync_native(&token_program_id, &seed_account)?);
+    }
+
+    Ok(instructions)
+}
+
+// move the tokens back to the wallet's ata and close the sub-account to reclaim rent.
+// `token_amount` is the current balance of the sub-account, wsol is simply closed
+pub fn sweep_seed_account_instructions(
+    base: &Pubkey,
+    seed: &str,
+    mint: &Pubkey,
+    token_amount: u64,
+) -> Result<Vec<Instruction>> {
+    let token_program_id = Pubkey::from_str(TOKEN_PROGRAM_ID)?;
+    let seed_account = derive_seed_account(base, seed)?;
+    let mut instructions = vec![];
+
+    if token_amount > 0 && *mint != WSOL_MINT {
+        let ata = get_associated_token_address(base, mint);
+        instructions.push(
+            create_associated_token_account_idempotent(base, base, mint, &token_program_id)
+        );
+        instructions.push(
+            spl_token::instruction::transfer(
+                &token_program_id,
+                &seed_account,
+                &ata,
+                base,
+                &[],
+                token_amount,
+            )?
+        );
+    }
+
+    instructions.push(
+        spl_token::instruction::close_account(
+            &token_program_id,
+            &seed_account,
+            base,
+            base,
+            &[],
+        )?
+    );
+
+    Ok(instructions)
+}
@@ -0,0 +1,41 @@
+use anyhow::{anyhow, Result};
+use dotenv::dotenv;
+use std::env;
+use std::sync::OnceLock;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::{fmt, prelude::*, reload, EnvFilter, Registry};
+
+// tracing goes to stdout like the old println! timings. spans log their duration when they
+// close, so "trade" / "build" / "sign" / "send" lines carry `time.busy` and `time.idle`.
+// the filter starts from LOG_LEVEL_RUST (default info) and can be changed from node
+
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+fn filter_handle() -> &'static reload::Handle<EnvFilter, Registry> {
+    FILTER_HANDLE.get_or_init(|| {
+        dotenv().ok();
+        let level = env::var("LOG_LEVEL_RUST").unwrap_or_else(|_| "info".to_string());
+        let filter = EnvFilter::try_new(&level).unwrap_or_else(|_| EnvFilter::new("info"));
+        let (filter, handle) = reload::Layer::new(filter);
+
+        // another subscriber may already be installed by the host, keep it
+        let _ = tracing_subscriber::registry()
+            .with(filter)
+            .with(fmt::layer().with_span_events(FmtSpan::CLOSE))
+            .try_init();
+        handle
+    })
+}
+
+pub fn init_tracing() {
+    filter_handle();
+}
+
+// `level` is a level ("debug") or a full filter ("info,hyper=warn")
+pub fn set_log_level(level: &str) -> Result<()> {
+    let filter = EnvFilter::try_new(level)
+        .map_err(|e| anyhow!("invalid log level {:?}: {}", level, e))?;
+    filter_handle()
+        .reload(filter)
+        .map_err(|e| anyhow!("failed to set log level: {}", e))
+}
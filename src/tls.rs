@@ -0,0 +1,107 @@
+use anyhow::{anyhow, Result};
+use base64::{Engine as _, engine::general_purpose};
+use rustls::client::{ServerCertVerified, ServerCertVerifier, WebPkiVerifier};
+use rustls::{Certificate, ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName};
+use solana_sdk::hash::hash;
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+// rustls config for relay clients that need extra CA roots or certificate pinning.
+// pins are base64 sha256 digests of the server's leaf certificate (DER)
+
+struct PinnedCertVerifier {
+    inner: WebPkiVerifier,
+    pins: Vec<[u8; 32]>,
+}
+
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        server_name: &ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        // the chain must still be valid, the pin only narrows which leaf is accepted
+        self.inner.verify_server_cert(end_entity, intermediates, server_name, scts, ocsp_response, now)?;
+
+        let digest = hash(&end_entity.0).to_bytes();
+        if self.pins.contains(&digest) {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::General(format!(
+                "certificate pin mismatch for {:?}, got sha256/{}",
+                server_name,
+                general_purpose::STANDARD.encode(digest),
+            )))
+        }
+    }
+}
+
+// accepts "sha256/<base64>" or the bare base64 digest
+pub fn parse_cert_pin(pin: &str) -> Result<[u8; 32]> {
+    let encoded = pin.trim().strip_prefix("sha256/").unwrap_or(pin.trim());
+    let bytes = general_purpose::STANDARD.decode(encoded)
+        .map_err(|e| anyhow!("invalid certificate pin {:?}: {}", pin, e))?;
+    bytes.try_into()
+        .map_err(|_| anyhow!("certificate pin {:?} is not a sha256 digest", pin))
+}
+
+fn load_root_store(ca_cert_paths: &[String]) -> Result<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+        OwnedTrustAnchor::from_subject_spki_name_constraints(
+            anchor.subject,
+            anchor.spki,
+            anchor.name_constraints,
+        )
+    }));
+
+    for path in ca_cert_paths {
+        let file = File::open(path)
+            .map_err(|e| anyhow!("failed to open CA file {}: {}", path, e))?;
+        let certs = rustls_pemfile::certs(&mut BufReader::new(file))
+            .map_err(|e| anyhow!("invalid certificate in {}: {}", path, e))?;
+        if certs.is_empty() {
+            return Err(anyhow!("no certificates found in {}", path));
+        }
+        for cert in certs {
+            roots.add(&Certificate(cert))
+                .map_err(|e| anyhow!("rejected certificate in {}: {}", path, e))?;
+        }
+    }
+    Ok(roots)
+}
+
+pub fn build_tls_config(
+    ca_cert_paths: &[String],
+    pinned_certs: &[String],
+    http2_prior_knowledge: bool,
+) -> Result<ClientConfig> {
+    let roots = load_root_store(ca_cert_paths)?;
+    let pins = pinned_certs.iter()
+        .map(|pin| parse_cert_pin(pin))
+        .collect::<Result<Vec<_>>>()?;
+
+    let builder = ClientConfig::builder().with_safe_defaults();
+    let mut config = if pins.is_empty() {
+        builder.with_root_certificates(roots).with_no_client_auth()
+    } else {
+        let inner = WebPkiVerifier::new(roots, None);
+        builder
+            .with_custom_certificate_verifier(Arc::new(PinnedCertVerifier { inner, pins }))
+            .with_no_client_auth()
+    };
+
+    // reqwest leaves alpn to us with a preconfigured config
+    config.alpn_protocols = if http2_prior_knowledge {
+        vec![b"h2".to_vec()]
+    } else {
+        vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+    };
+    Ok(config)
+}
@@ -3,13 +3,10 @@ use solana_sdk::{
     pubkey::Pubkey,
     signature::{Keypair, Signer},
     transaction::Transaction,
-    system_instruction,
     commitment_config::CommitmentConfig,
     compute_budget::ComputeBudgetInstruction,
-    native_token::LAMPORTS_PER_SOL,
-    message::v0::{Message, MessageAddressTableLookup},
+    message::v0::Message,
     transaction::VersionedTransaction,
-    signature::Signature
 };
 
 use solana_program::{
@@ -19,8 +16,10 @@ use solana_program::{
 
 use solana_client::{
     rpc_client::RpcClient,
-    rpc_config::RpcSendTransactionConfig,
+    rpc_config::RpcProgramAccountsConfig,
+    rpc_filter::{Memcmp, RpcFilterType},
 };
+use solana_program::program_pack::Pack;
 use anyhow::{Error, Result, Context, bail};
 
 use solana_sdk::message::VersionedMessage;
@@ -32,9 +31,24 @@ use std::str::FromStr;
 use crate::request::ThirdPartySender;
 use crate::request::SignedTransaction;
 use crate::contracts::group_validate_compile::validate_compile;
-use crate::contracts::pumpfun_proxy::PumpfunProxy;
+use crate::contracts::pumpfun_proxy::{PumpfunProxy, BondingCurveInfo};
+use crate::contracts::pumpfun::Pumpfun;
+use crate::contracts::pumpfun_global::{get_fee_params, get_pumpfun_global};
+use crate::contracts::pumpswap::PumpSwap;
+use crate::contracts::raydium_amm::RaydiumAmm;
+use crate::contracts::wsol::{get_wsol_account, wrap_sol_instructions, unwrap_sol_instruction};
 use crate::contracts::jito_trick::jito_trick_trade;
 use crate::contracts::remain_balance_check::remain_balance_check;
+use crate::bonding_curve_cache::{get_bonding_curve, get_bonding_curve_pda, is_bonding_curve_complete};
+use crate::keep_alive::is_relay_healthy;
+use crate::region_probe::resolve_relay_urls;
+use crate::ata_cache::{is_known_ata, mark_known_ata, forget_ata};
+use crate::seed_account::{
+    create_seed_account_instructions,
+    derive_seed_account,
+    resolve_seed_account,
+    sweep_seed_account_instructions,
+};
 use crate::configs::global::*;
 use crate::configs::bribe::*;
 use base64::{Engine as _, engine::general_purpose};
@@ -42,19 +56,28 @@ use base64::{engine::general_purpose::STANDARD, Engine};
 use anyhow::{anyhow};
 
 use serde::Deserialize;
-use crate::utils::{SwapParam, build_tip_transfer_instruction, SolAccountStruct, TYPE_JITO, TYPE_NEXTBLOCK, TYPE_TEMPORAL, TYPE_BLOXROUTE, TYPE_0SLOT_TRADE, TYPE_NODE1_ME};
+use crate::utils::{SwapParam, SellMode, scale_amount_out, TradeReport, EndpointOutcome, LaunchParam, BundleBuyParam, BundleBuyOutcome, build_tip_transfer_instruction, SolAccountStruct, TYPE_JITO, TYPE_NEXTBLOCK, TYPE_TEMPORAL, TYPE_BLOXROUTE, TYPE_0SLOT_TRADE, TYPE_NODE1_ME};
 
 use anchor_lang::Discriminator;
 use anchor_spl::token::accessor::mint;
 use spl_associated_token_account::{
     get_associated_token_address,
-    instruction::create_associated_token_account,
+    instruction::create_associated_token_account_idempotent,
 };
 use futures::future::join_all;
 use std::env;
 use solana_program::hash::hash;
 use tokio::runtime::Handle;
 use dotenv::dotenv;
+use tracing::{debug, info, info_span, warn, Instrument};
+use crate::metrics::{observe_relay_send, observe_trade_stage};
+use crate::latency::{LatencyBreakdown, measure, record_relay_http, record_stage, timed_rpc, Stage};
+use crate::telemetry::init_tracing;
+use crate::secret::redact_url;
+use crate::keystore::parse_secret_key;
+
+const MAX_BUNDLE_TRANSACTIONS: usize = 5;
+const CLOSE_ACCOUNTS_PER_TX: usize = 20;
 
 #[derive(Error, Debug)]
 pub enum TransactionError {
@@ -68,6 +91,13 @@ pub enum TransactionError {
     InvalidPrivateKey,
 }
 
+// a bonding curve filled by the buys of one bundle in order, each quoted on the state before it
+pub struct BundleCurve {
+    pub mint: Pubkey,
+    pub state: BondingCurveInfo,
+    pub fee_bps: u64,
+}
+
 pub struct TransactionBuilder {
     rpc_url: String,
     rpc_client: RpcClient,
@@ -76,6 +106,7 @@ pub struct TransactionBuilder {
 
 impl TransactionBuilder {
     pub fn new(rpc_url: String, fee_payer: Keypair) -> Self {
+        init_tracing();
         TransactionBuilder {
             rpc_url: rpc_url.clone(),
             rpc_client: RpcClient::new_with_commitment(rpc_url.clone(), CommitmentConfig::confirmed()),
@@ -157,6 +188,260 @@ impl TransactionBuilder {
         Ok(instructions)
     }
 
+    pub async fn build_pumpfun_instructions(
+        &self,
+        param: &SwapParam,
+        is_buy: bool,
+        token_coin: &Pubkey,
+        token_ata_account_addr: &Pubkey,
+        bonding_curve: &BondingCurveInfo,
+    ) -> anyhow::Result<Vec<Instruction>> {
+
+        let mut instructions = vec![];
+
+        let pumpfun_builder = Pumpfun::new(Pubkey::from_str(PUMPFUN_PROGRAM_ID)?);
+
+        let (fee_recipient, fee_bps) = timed_rpc(|| get_fee_params(&self.rpc_client));
+
+        let creator_vault_pubkey;
+        let creator_vault: Option<&Pubkey> = match &param.creator_vault {
+            Some(value) => {
+                creator_vault_pubkey = Pubkey::from_str(value)?;
+                Some(&creator_vault_pubkey)
+            },
+            None => None
+        };
+
+        let inputs = pumpfun_builder.get_account_inputs(
+            token_coin,
+            token_ata_account_addr,
+            &self.fee_payer.pubkey(),
+            &fee_recipient,
+            &bonding_curve.creator,
+            creator_vault,
+        )?;
+
+        if is_buy {
+            //amount_in is the max sol cost, amount_out the token amount unless it is quoted here
+            let token_amount = if param.calculate_amount_out.unwrap_or(false) {
+                let quoted = bonding_curve.get_buy_token_amount(param.amount_in, fee_bps);
+                (quoted as u128 * 10_000u128.saturating_sub(param.slippage_amount as u128) / 10_000) as u64
+            } else {
+                param.amount_out
+            };
+            if token_amount == 0 {
+                bail!("pump.fun buy token amount is zero");
+            }
+
+            instructions.push(
+                pumpfun_builder.get_buy_instruction(&token_amount, &param.amount_in, &inputs)?
+            );
+        }
+        else {
+            instructions.push(
+                pumpfun_builder.get_sell_instruction(&param.amount_in, &param.amount_out, &inputs)?
+            );
+        }
+
+        Ok(instructions)
+    }
+
+    pub fn get_bonding_curve_state(
+        &self,
+        token_coin: &Pubkey,
+    ) -> Result<Option<BondingCurveInfo>> {
+        timed_rpc(|| get_bonding_curve(&self.rpc_client, token_coin))
+    }
+
+    pub async fn build_pumpswap_instructions(
+        &self,
+        param: &SwapParam,
+        is_buy: bool,
+        token_coin: &Pubkey,
+        token_ata_account_addr: &Pubkey,
+    ) -> anyhow::Result<Vec<Instruction>> {
+
+        let mut instructions = vec![];
+
+        let pumpswap_builder = PumpSwap::new(
+            RpcClient::new_with_commitment(
+                self.rpc_url.clone(),
+                CommitmentConfig::processed()
+            ),
+            Pubkey::from_str(PUMPFUN_PROGRAM_ID)?,
+            Pubkey::from_str(PUMPSWAP_PROGRAM_ID)?,
+        );
+
+        let pool_addr = pumpswap_builder.get_pool_pda(token_coin);
+        let pool = pumpswap_builder.get_pool_info(&pool_addr)?;
+        let quote = pumpswap_builder.get_quote(&pool)?;
+        let payer = self.fee_payer.pubkey();
+
+        if is_buy {
+            //amount_in is the sol budget, the base amount is quoted and reduced by slippage
+            let base_amount_out = quote.buy_base_out(param.amount_in);
+            let base_amount_out = (base_amount_out as u128
+                * 10_000u128.saturating_sub(param.slippage_amount as u128)
+                / 10_000) as u64;
+            if base_amount_out == 0 {
+                bail!("PumpSwap quote returned zero tokens for {} lamports", param.amount_in);
+            }
+
+            instructions.extend(wrap_sol_instructions(&payer, param.amount_in));
+            instructions.push(
+                pumpswap_builder.get_buy_instruction(
+                    &pool_addr,
+                    &pool,
+                    &base_amount_out,
+                    &param.amount_in,
+                    &payer,
+                    token_ata_account_addr,
+                )?
+            );
+        }
+        else {
+            instructions.extend(wrap_sol_instructions(&payer, 0));
+            instructions.push(
+                pumpswap_builder.get_sell_instruction(
+                    &pool_addr,
+                    &pool,
+                    &param.amount_in,
+                    &param.amount_out,
+                    &payer,
+                    token_ata_account_addr,
+                )?
+            );
+        }
+        instructions.push(unwrap_sol_instruction(&payer));
+
+        Ok(instructions)
+    }
+
+    pub async fn build_raydium_instructions(
+        &self,
+        param: &SwapParam,
+        is_buy: bool,
+        token_ata_account_addr: &Pubkey,
+    ) -> anyhow::Result<Vec<Instruction>> {
+
+        let mut instructions = vec![];
+
+        let market_id = match &param.market_id {
+            Some(value) => Pubkey::from_str(value)?,
+            None => bail!("market_id is required for raydium trades"),
+        };
+
+        let raydium_builder = RaydiumAmm::new(
+            RpcClient::new_with_commitment(
+                self.rpc_url.clone(),
+                CommitmentConfig::processed()
+            ),
+            Pubkey::from_str(RAYDIUM_AMM_V4_PROGRAM_ID)?,
+        );
+
+        let pool = raydium_builder.get_pool(&market_id)?;
+        let quote = raydium_builder.get_quote(&pool.amm)?;
+        let payer = self.fee_payer.pubkey();
+        let wsol_account = get_wsol_account(&payer);
+
+        if is_buy {
+            //snipe guard, only buy while the pool is still shallow
+            if let Some(max_sol_reserve) = param.snipe_raydium_sol_reserve {
+                if quote.sol_reserve() > max_sol_reserve {
+                    bail!(
+                        "raydium pool sol reserve {} above snipe threshold {}",
+                        quote.sol_reserve(),
+                        max_sol_reserve
+                    );
+                }
+            }
+
+            let amount_out = quote.amount_out(param.amount_in, true);
+            let min_amount_out = (amount_out as u128
+                * 10_000u128.saturating_sub(param.slippage_amount as u128)
+                / 10_000) as u64;
+
+            instructions.extend(wrap_sol_instructions(&payer, param.amount_in));
+            instructions.push(
+                raydium_builder.get_swap_base_in_instruction(
+                    &pool,
+                    &param.amount_in,
+                    &min_amount_out,
+                    &wsol_account,
+                    token_ata_account_addr,
+                    &payer,
+                )?
+            );
+        }
+        else {
+            instructions.extend(wrap_sol_instructions(&payer, 0));
+            instructions.push(
+                raydium_builder.get_swap_base_in_instruction(
+                    &pool,
+                    &param.amount_in,
+                    &param.amount_out,
+                    token_ata_account_addr,
+                    &wsol_account,
+                    &payer,
+                )?
+            );
+        }
+        instructions.push(unwrap_sol_instruction(&payer));
+
+        Ok(instructions)
+    }
+
+    pub async fn build_swap_instructions(
+        &self,
+        param: &SwapParam,
+        is_buy: bool,
+        token_coin: &Pubkey,
+        token_ata_account_addr: &Pubkey,
+        buy_once: bool,
+    ) -> anyhow::Result<Vec<Instruction>> {
+
+        match param.target_pool.as_str() {
+            TARGET_POOL_RAYDIUM => {
+                return self.build_raydium_instructions(param, is_buy, token_ata_account_addr).await;
+            },
+            TARGET_POOL_PUMPSWAP => {
+                return self.build_pumpswap_instructions(param, is_buy, token_coin, token_ata_account_addr).await;
+            },
+            _ => {}
+        }
+
+        //the bonding curve is closed for trading once it migrated to PumpSwap. curves seen
+        //complete are remembered, otherwise the state comes from the streamed cache and only
+        //a cache miss reads the account. the direct path reuses it for its quote
+        let migrated = is_bonding_curve_complete(&get_bonding_curve_pda(token_coin)?);
+        let bonding_curve = if migrated { None } else { self.get_bonding_curve_state(token_coin)? };
+
+        if migrated || bonding_curve.as_ref().is_some_and(|info| info.complete) {
+            info!(mint = %token_coin, "bonding curve complete, route to PumpSwap");
+            self.build_pumpswap_instructions(param, is_buy, token_coin, token_ata_account_addr).await
+        }
+        else if param.pumpfun_direct.unwrap_or(false) || PUMPFUN_PROXY_PROGRAM_ID.is_empty() {
+            let bonding_curve = bonding_curve
+                .ok_or_else(|| anyhow!("bonding curve not found for {}", token_coin))?;
+            self.build_pumpfun_instructions(
+                param,
+                is_buy,
+                token_coin,
+                token_ata_account_addr,
+                &bonding_curve,
+            ).await
+        }
+        else {
+            self.build_proxy_contract_instructions(
+                param,
+                is_buy,
+                token_coin,
+                token_ata_account_addr,
+                buy_once,
+            ).await
+        }
+    }
+
     pub async fn build_pub_instructions(
         &self,
         swap_param: &SwapParam,
@@ -230,31 +515,41 @@ impl TransactionBuilder {
         //     &token_coin,
         // );
 
-        if is_buy {
-            let token_ata_instruction = create_associated_token_account(
-                &self.fee_payer.pubkey(),
-                &self.fee_payer.pubkey(),
-                &token_coin,
-                &Pubkey::from_str(TOKEN_PROGRAM_ID)?
-            );
-
-            if buy_once {
-                instructions.push(token_ata_instruction);
+        if let (true, Some(sol_account)) = (is_buy, &param.sol_account) {
+            //trading from a seed-derived sub-account instead of the ata. there is no idempotent
+            //create_account_with_seed, so the account is looked up (buy_once skips the cache)
+            let exists = (!buy_once && is_known_ata(token_ata_account_addr)) ||
+                timed_rpc(|| self.rpc_client.get_account_with_commitment(
+                    token_ata_account_addr,
+                    CommitmentConfig::processed(),
+                ).map_err(|e| TransactionError::RpcError(e.to_string())))?
+                    .value
+                    .is_some();
+            if exists {
+                mark_known_ata(token_ata_account_addr);
             }
-            else{
-                match self.rpc_client.get_account(
-                    &token_ata_account_addr
-                ) {
-                    Ok(account_info) => {
-                        Some(1)
-                    },
-                    Err(e) => {
-                        instructions.push(token_ata_instruction);
-                        None
-                    }
-                };
+            else {
+                instructions.extend(
+                    create_seed_account_instructions(
+                        &self.fee_payer.pubkey(),
+                        &sol_account.seed,
+                        token_coin,
+                        0,
+                    )?
+                );
             }
         }
+        else if is_buy && (buy_once || !is_known_ata(token_ata_account_addr)) {
+            //idempotent, so a concurrent create of the same ata does not fail the trade
+            instructions.push(
+                create_associated_token_account_idempotent(
+                    &self.fee_payer.pubkey(),
+                    &self.fee_payer.pubkey(),
+                    &token_coin,
+                    &Pubkey::from_str(TOKEN_PROGRAM_ID)?
+                )
+            );
+        }
 
         //validate_compile
         let token_account_out = if is_buy { token_ata_account_addr } else { &native_mint };
@@ -296,23 +591,6 @@ impl TransactionBuilder {
             instructions.push(jito_trick_instruction);
         }
 
-        //classic transfer
-        if param.token_balance.is_some() && param.token_mint.is_some() {
-            let token_balance = param.token_balance.unwrap();
-            let token_mint = Pubkey::from_str(&param.token_mint.unwrap())?;
-            let token_account_addr = get_associated_token_address(
-                &self.fee_payer.pubkey(),
-                &token_mint,
-            );
-            let transfer_instruction = system_instruction::transfer(
-                &token_account_addr,
-                &token_account_addr,
-                token_balance,
-            );
-
-            instructions.push(transfer_instruction);
-        }
-
         //remain_balance_check
         if param.remain_token_balance.is_some() {
             let remain_token_balance = param.remain_token_balance.unwrap();
@@ -377,12 +655,132 @@ impl TransactionBuilder {
         ]
     }
 
+    // plain rpc submission for housekeeping transactions that do not need the relays
+    pub fn send_rpc_tx(
+        &self,
+        instructions: Vec<Instruction>,
+    ) -> Result<String> {
+        let recent_blockhash = self.rpc_recent_block_hash();
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&self.fee_payer.pubkey()),
+            &[&self.fee_payer],
+            recent_blockhash,
+        );
+        let signature = self.rpc_client
+            .send_and_confirm_transaction(&transaction)
+            .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+        Ok(signature.to_string())
+    }
+
+    pub fn create_seed_account(
+        &self,
+        seed: &str,
+        mint: &Pubkey,
+        lamports: u64,
+    ) -> Result<(String, String)> {
+        let seed_account = derive_seed_account(&self.fee_payer.pubkey(), seed)?;
+        let instructions = create_seed_account_instructions(
+            &self.fee_payer.pubkey(),
+            seed,
+            mint,
+            lamports,
+        )?;
+        let signature = self.send_rpc_tx(instructions)?;
+        Ok((seed_account.to_string(), signature))
+    }
+
+    // close every empty spl token account of the wallet to reclaim rent.
+    // returns the closed accounts and the signatures of the batches that were sent
+    pub fn close_empty_token_accounts(
+        &self,
+    ) -> Result<(Vec<String>, Vec<String>)> {
+        let token_program_id = Pubkey::from_str(TOKEN_PROGRAM_ID)?;
+        let owner = self.fee_payer.pubkey();
+
+        let accounts = self.rpc_client
+            .get_program_accounts_with_config(
+                &token_program_id,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![
+                        RpcFilterType::DataSize(spl_token::state::Account::LEN as u64),
+                        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(32, owner.as_ref())),
+                    ]),
+                    ..RpcProgramAccountsConfig::default()
+                },
+            )
+            .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+
+        let empty_accounts: Vec<Pubkey> = accounts.into_iter()
+            .filter(|(_, account)| {
+                spl_token::state::Account::unpack(&account.data)
+                    .map(|token_account| token_account.amount == 0)
+                    .unwrap_or(false)
+            })
+            .map(|(pubkey, _)| pubkey)
+            .collect();
+
+        let mut closed = vec![];
+        let mut signatures = vec![];
+        for batch in empty_accounts.chunks(CLOSE_ACCOUNTS_PER_TX) {
+            let mut instructions = vec![];
+            for account in batch {
+                instructions.push(
+                    spl_token::instruction::close_account(
+                        &token_program_id,
+                        account,
+                        &owner,
+                        &owner,
+                        &[],
+                    )?
+                );
+            }
+
+            match self.send_rpc_tx(instructions) {
+                Ok(signature) => {
+                    for account in batch {
+                        forget_ata(account);
+                        closed.push(account.to_string());
+                    }
+                    signatures.push(signature);
+                },
+                Err(e) => warn!("close token accounts batch failed: {}", e),
+            }
+        }
+
+        Ok((closed, signatures))
+    }
+
+    pub fn sweep_seed_account(
+        &self,
+        seed: &str,
+        mint: &Pubkey,
+    ) -> Result<String> {
+        let seed_account = derive_seed_account(&self.fee_payer.pubkey(), seed)?;
+        let token_amount: u64 = self.rpc_client
+            .get_token_account_balance(&seed_account)
+            .map_err(|e| TransactionError::RpcError(e.to_string()))?
+            .amount
+            .parse()?;
+        let instructions = sweep_seed_account_instructions(
+            &self.fee_payer.pubkey(),
+            seed,
+            mint,
+            token_amount,
+        )?;
+        self.send_rpc_tx(instructions)
+    }
+
     pub fn rpc_recent_block_hash(
         &self,
     ) -> Hash {
-        self.rpc_client
+        let _span = info_span!("blockhash").entered();
+        let start = Instant::now();
+        let recent_blockhash = self.rpc_client
             .get_latest_blockhash()
-            .expect("rpc_recent_block_hash failed")
+            .expect("rpc_recent_block_hash failed");
+        record_stage(Stage::Blockhash, start.elapsed());
+        recent_blockhash
     }
 
     pub fn get_recent_block_hash(
@@ -428,35 +826,40 @@ impl TransactionBuilder {
                 else { self.rpc_recent_block_hash() }
             }
             else {
-                println!("11111111");
+                debug!(bundle_id, "unknown relay, blockhash from rpc");
                 self.rpc_recent_block_hash()
             }
         }
         else {
             //normal trade
-            println!("else 88888");
             self.rpc_recent_block_hash()
         }
     }
 
 
-    pub async fn send_tx(
+    // append tip and compute price for the relay, then compile and sign a v0 transaction.
+    // `payer` pays fee and tip, `extra_signers` co-sign (e.g. a fresh mint keypair)
+    pub fn sign_tx(
         &self,
         swap_param: &SwapParam,
         mut instructions: Vec<Instruction>,
         bundle_type: Option<u8>,
-        block_engine_url: String,
-        sender: &ThirdPartySender,
-    ) -> Result<Vec<String>> {
+        payer: &Keypair,
+        extra_signers: &[&Keypair],
+        with_tip: bool,
+    ) -> Result<VersionedTransaction> {
+
+        let _span = info_span!("sign", relay = get_sender_name(bundle_type.unwrap_or(u8::MAX))).entered();
 
         let recent_blockhash = self.get_recent_block_hash(bundle_type, swap_param);
+        let start = Instant::now();
 
-        println!("recent_blockhash {:?}", recent_blockhash);
+        debug!(%recent_blockhash, "recent blockhash");
 
-        if let Some(sender_type) = bundle_type {
+        if let (Some(sender_type), true) = (bundle_type, with_tip) {
             let tip_transfer_instruction = build_tip_transfer_instruction(
                 swap_param,
-                &self.fee_payer.pubkey(),
+                &payer.pubkey(),
                 &sender_type,
             );
             instructions.extend(vec![tip_transfer_instruction]);
@@ -466,60 +869,94 @@ impl TransactionBuilder {
 
         //versioned tx
         let message = Message::try_compile(
-            &self.fee_payer.pubkey(),
+            &payer.pubkey(),
             &instructions,
             &[],
             recent_blockhash,
         )?;
+        let mut signers: Vec<&Keypair> = vec![payer];
+        signers.extend(extra_signers.iter().copied().filter(|k| k.pubkey() != payer.pubkey()));
         let transaction = VersionedTransaction::try_new(
             VersionedMessage::V0(message),
-            &[&self.fee_payer],
+            &signers,
         )?;
+        record_stage(Stage::Sign, start.elapsed());
+
+        Ok(transaction)
+    }
+
+    pub async fn send_tx(
+        &self,
+        swap_param: &SwapParam,
+        instructions: Vec<Instruction>,
+        bundle_type: Option<u8>,
+        block_engine_url: String,
+        sender: &ThirdPartySender,
+        extra_signers: &[&Keypair],
+    ) -> Result<Vec<String>> {
 
+        let transaction = self.sign_tx(
+            swap_param,
+            instructions,
+            bundle_type,
+            &self.fee_payer,
+            extra_signers,
+            true,
+        )?;
 
-        // println!("Instructions: {:?} {:?}", instructions, recent_blockhash);
+        self.send_signed_tx(&transaction, bundle_type, block_engine_url, sender).await
+    }
+
+    #[tracing::instrument(name = "send", skip_all, fields(relay = get_sender_name(bundle_type.unwrap_or(u8::MAX))))]
+    pub async fn send_signed_tx(
+        &self,
+        transaction: &VersionedTransaction,
+        bundle_type: Option<u8>,
+        block_engine_url: String,
+        sender: &ThirdPartySender,
+    ) -> Result<Vec<String>> {
 
         let http_time = Instant::now();
 
         //start sending
         let response = if Some(TYPE_JITO) == bundle_type {
             sender.send_jito(
-                &transaction,
+                transaction,
                 &block_engine_url,
                 JITO_WALLET_KEY,
             ).await
         }
         else if Some(TYPE_BLOXROUTE) == bundle_type {
             sender.send_bloxroute(
-                &transaction,
+                transaction,
                 &block_engine_url,
                 BLOXROUTE_KEY,
             ).await
         }
         else if Some(TYPE_TEMPORAL) == bundle_type {
             sender.send_temporal(
-                &transaction,
+                transaction,
                 &block_engine_url,
                 TEMPORAL_KEY,
             ).await
         }
         else if Some(TYPE_NEXTBLOCK) == bundle_type {
             sender.send_nextblock(
-                &transaction,
+                transaction,
                 &block_engine_url,
                 SLOTE0_TRADE_KEY,
             ).await
         }
         else if Some(TYPE_0SLOT_TRADE) == bundle_type {
             sender.send_0slot(
-                &transaction,
+                transaction,
                 &block_engine_url,
                 SLOTE0_TRADE_KEY,
             ).await
         }
         else if Some(TYPE_NODE1_ME) == bundle_type {
             sender.send_node1(
-                &transaction,
+                transaction,
                 &block_engine_url,
                 NODE1_ME_KEY,
             ).await
@@ -528,7 +965,12 @@ impl TransactionBuilder {
             bail!("Invalid bundle type");
         };
 
-        println!("http_time {:?}", http_time.elapsed());
+        let relay = get_sender_name(bundle_type.unwrap_or(u8::MAX));
+        observe_relay_send(relay, response.is_ok(), http_time.elapsed());
+        match &response {
+            Ok(_) => debug!(elapsed = ?http_time.elapsed(), "sent"),
+            Err(e) => warn!(elapsed = ?http_time.elapsed(), "send failed: {}", e),
+        }
 
         let result = vec![block_engine_url, response?];
 
@@ -543,8 +985,32 @@ impl TransactionBuilder {
         &self,
         swap_param: &SwapParam,
     ) -> Result<Vec<String>> {
+        let report = self.trade_detailed(swap_param).await?;
+        Ok(report.to_responses())
+    }
+
+    #[tracing::instrument(name = "trade", skip_all, fields(token_in = %swap_param.token_in, token_out = %swap_param.token_out))]
+    pub async fn trade_detailed(
+        &self,
+        swap_param: &SwapParam,
+    ) -> Result<TradeReport> {
 
         let start = Instant::now();
+        let (report, mut latency) = measure(self.build_and_send(swap_param)).await;
+        let mut report = report?;
+
+        observe_trade_stage("total", start.elapsed());
+        latency.total_ms = start.elapsed().as_secs_f64() * 1000.0;
+        report.latency = latency;
+        Ok(report)
+    }
+
+    async fn build_and_send(
+        &self,
+        swap_param: &SwapParam,
+    ) -> Result<TradeReport> {
+
+        let parse_start = Instant::now();
 
         dotenv().ok();
         let mut param = swap_param.clone();
@@ -558,144 +1024,146 @@ impl TransactionBuilder {
         let token_coin: Pubkey = if is_buy { Pubkey::from_str(&param.token_out)? } else { Pubkey::from_str(&param.token_in)? };
         let buy_once = param.buy_once.unwrap_or_else(|| false);
 
-        let token_ata_account_addr = get_associated_token_address(
-            &self.fee_payer.pubkey(),
-            &token_coin,
-        );
-
-        println!("trade before {:?}", start.elapsed());
-
-        let pub_instructions = self.build_pub_instructions(
-            &param,
-            is_buy,
-            &token_coin,
-            &token_ata_account_addr,
-            buy_once,
-        ).await.unwrap();
+        let token_ata_account_addr = match &param.sol_account {
+            Some(sol_account) => resolve_seed_account(&self.fee_payer.pubkey(), sol_account)?,
+            None => get_associated_token_address(
+                &self.fee_payer.pubkey(),
+                &token_coin,
+            ),
+        };
 
-        println!("trade pub instruction {:?}", start.elapsed());
+        //size the sell from the live balance
+        let sell_mode = match &param.sell_mode {
+            Some(mode) if !is_buy => Some(SellMode::parse(mode)?),
+            _ => None,
+        };
+        record_stage(Stage::Parse, parse_start.elapsed());
+
+        if let Some(sell_mode) = sell_mode {
+            let balance: u64 = timed_rpc(|| self.rpc_client.get_token_account_balance(&token_ata_account_addr)
+                .map_err(|e| TransactionError::RpcError(e.to_string())))?
+                .amount
+                .parse()?;
+            mark_known_ata(&token_ata_account_addr);
+            let quoted_in = param.amount_in;
+            param.amount_in = sell_mode.resolve(balance);
+            if param.amount_in == 0 {
+                bail!("nothing to sell, balance {} with sell_mode {:?}", balance, sell_mode);
+            }
+            //amount_out is the min out for the amount_in js quoted, keep the same price floor
+            param.amount_out = scale_amount_out(param.amount_out, quoted_in, param.amount_in);
+        }
 
-        let custom_instructions = self.build_proxy_contract_instructions(
-            &param,
-            is_buy,
-            &token_coin,
-            &token_ata_account_addr,
-            buy_once,
-        ).await.unwrap();
+        let build_start = Instant::now();
+        let (pub_instructions, custom_instructions) = async {
+            let pub_instructions = self.build_pub_instructions(
+                &param,
+                is_buy,
+                &token_coin,
+                &token_ata_account_addr,
+                buy_once,
+            ).await?;
 
-        println!("trade custom instruction {:?}", start.elapsed());
+            let custom_instructions = self.build_swap_instructions(
+                &param,
+                is_buy,
+                &token_coin,
+                &token_ata_account_addr,
+                buy_once,
+            ).await?;
 
-        // println!("pub_instructions {:?}", pub_instructions);
-        // println!("custom_instructions {:?}", custom_instructions);
+            Ok::<_, Error>((pub_instructions, custom_instructions))
+        }.instrument(info_span!("build")).await?;
+        record_stage(Stage::Build, build_start.elapsed());
 
         let mut instructions = vec![];
         instructions.extend(pub_instructions);
         instructions.extend(custom_instructions);
 
-        println!("trade fuck 1 {:?}", start.elapsed());
+        //the whole balance is gone, reclaim the account rent
+        if sell_mode.is_some_and(|mode| mode.closes_account()) {
+            instructions.push(
+                spl_token::instruction::close_account(
+                    &Pubkey::from_str(TOKEN_PROGRAM_ID)?,
+                    &token_ata_account_addr,
+                    &self.fee_payer.pubkey(),
+                    &self.fee_payer.pubkey(),
+                    &[],
+                )?
+            );
+            forget_ata(&token_ata_account_addr);
+        }
+
+        self.fan_out_detailed(&param, instructions, &[]).await
+    }
 
-        // let recent_blockhash = self.rpc_client
-        //     .get_latest_blockhash()
-        //     .map_err(|e| TransactionError::RpcError(e.to_string()))?;
+    // send the same instructions through every relay that is configured and bribed
+    pub async fn fan_out(
+        &self,
+        param: &SwapParam,
+        instructions: Vec<Instruction>,
+        extra_signers: &[&Keypair],
+    ) -> Result<Vec<String>> {
+        let report = self.fan_out_detailed(param, instructions, extra_signers).await?;
+        Ok(report.to_responses())
+    }
+
+    // one signed transaction per relay, sent to every enabled region of that relay at once.
+    // regions of a relay share the transaction, so they land at most once
+    pub async fn fan_out_detailed(
+        &self,
+        param: &SwapParam,
+        instructions: Vec<Instruction>,
+        extra_signers: &[&Keypair],
+    ) -> Result<TradeReport> {
 
         let sender = match ThirdPartySender::new(){
             Ok(sender) => sender,
             Err(e) => {
-                eprintln!("Failed to create sender: {}", e);
+                warn!("Failed to create sender: {}", e);
                 return Err(e);
             }
         };
 
-        println!("trade fuck 2 {:?}", start.elapsed());
-        // let duration = start.elapsed();
-        // println!("trade cost: {:?}", duration);
-
-        let mut execute_list = vec![];
-
-        let bl_jito: Option<String> = env::var("STRATEGY_BIG_BLOCKENGINE_RUST").ok();
-        let bl_nextblock: Option<String> = env::var("NEXTBLOCK_BLOCKENGINE_RUST").ok();
-        let bl_temporal: Option<String> = env::var("TEMPORAL_BLOCKENGINE_RUST").ok();
-        let bl_bloxroute: Option<String> = env::var("BLOXROUTE_BLOCKENGINE_RUST").ok();
-        let bl_0slot: Option<String> = env::var("SLOT0_TRADE_BLOCKENGINE_RUST").ok();
-        let bl_node1me: Option<String> = env::var("NODE1_ME_BLOCKENGINE_RUST").ok();
+        //regions from block_engine_locate or the prober, relays the keep-alive service saw failing are left out.
+        //when every region of a relay is marked failing they are all tried, the health check may be stale
+        let locate = param.block_engine_locate.as_deref();
+        let region_count = param.region_fan_out.unwrap_or(1).max(1) as usize;
+        let urls = |relay: u8| -> Vec<String> {
+            let all = resolve_relay_urls(relay, locate, region_count);
+            let healthy: Vec<String> = all.iter()
+                .filter(|url| is_relay_healthy(url))
+                .cloned()
+                .collect();
+            if healthy.is_empty() { all } else { healthy }
+        };
+        let bl_jito = urls(TYPE_JITO);
+        let bl_nextblock = urls(TYPE_NEXTBLOCK);
+        let bl_temporal = urls(TYPE_TEMPORAL);
+        let bl_bloxroute = urls(TYPE_BLOXROUTE);
+        let bl_0slot = urls(TYPE_0SLOT_TRADE);
+        let bl_node1me = urls(TYPE_NODE1_ME);
 
-        println!("trade send before {:?}", start.elapsed());
+        let mut legs: Vec<(u8, Vec<String>)> = vec![];
 
         //node1me
-        if send_node1me_or_not(
-            param.nodeme_bundle_bribe,
-            &bl_node1me,
-        ) {
-            let bl = bl_node1me.as_ref().unwrap();
-            execute_list.push(
-                self.send_tx(
-                    &param,
-                    instructions.clone(),
-                    Some(TYPE_NODE1_ME),
-                    bl.clone(),
-                    &sender
-                )
-            );
+        if send_node1me_or_not(param.nodeme_bundle_bribe, &bl_node1me.first().cloned()) {
+            legs.push((TYPE_NODE1_ME, bl_node1me));
         }
 
         //0slot.trade
-        if send_slot0_trade_or_not(
-            param.slot0_trade_bundle_bribe,
-            &bl_0slot,
-        ) {
-            let bl = bl_0slot.as_ref().unwrap();
-            execute_list.push(
-                self.send_tx(
-                    &param,
-                    instructions.clone(),
-                    Some(TYPE_0SLOT_TRADE),
-                    bl.clone(),
-                    &sender
-                )
-            );
+        if send_slot0_trade_or_not(param.slot0_trade_bundle_bribe, &bl_0slot.first().cloned()) {
+            legs.push((TYPE_0SLOT_TRADE, bl_0slot));
         }
 
         //bloxroute
-        if send_bloxroute_or_not(
-            param.bloxroute_bundle_bribe,
-            &bl_bloxroute,
-        ) {
-            let bl = bl_bloxroute.as_ref().unwrap();
-            // let mut bloxroute_ins = instructions.clone();
-            //
-            // let memo_ins = Instruction {
-            //     program_id: Pubkey::from_str(BLOXROUTE_TRADER_API_MEMO_PROGRAM)?,
-            //     accounts: Vec::new(),
-            //     data: BLOXROUTE_MEMO_MARKER_MSG.as_bytes().to_vec(),
-            // };
-            // bloxroute_ins.push(memo_ins);
-
-            execute_list.push(
-                self.send_tx(
-                    &param,
-                    instructions.clone(),
-                    Some(TYPE_BLOXROUTE),
-                    bl.clone(),
-                    &sender
-                )
-            );
+        if send_bloxroute_or_not(param.bloxroute_bundle_bribe, &bl_bloxroute.first().cloned()) {
+            legs.push((TYPE_BLOXROUTE, bl_bloxroute));
         }
 
         // temporal
-        if send_temporal_or_not(
-            param.temporal_bundle_bribe,
-            &bl_temporal,
-        ) {
-            let bl = bl_temporal.as_ref().unwrap();
-            execute_list.push(
-                self.send_tx(
-                    &param,
-                    instructions.clone(),
-                    Some(TYPE_TEMPORAL),
-                    bl.clone(),
-                    &sender
-                )
-            );
+        if send_temporal_or_not(param.temporal_bundle_bribe, &bl_temporal.first().cloned()) {
+            legs.push((TYPE_TEMPORAL, bl_temporal));
         }
 
         //jito
@@ -705,68 +1173,439 @@ impl TransactionBuilder {
             param.simulate_bundle_bribe,
             param.second_block_bundle_bribe,
             param.land_bundle_bribe,
-            &bl_jito,
+            &bl_jito.first().cloned(),
         ) {
-            let bl = bl_jito.as_ref().unwrap();
-            execute_list.push(
-                self.send_tx(
-                    &param,
-                    instructions.clone(),
-                    Some(TYPE_JITO),
-                    bl.clone(),
-                    &sender
-                )
-            );
+            legs.push((TYPE_JITO, bl_jito));
         }
 
         //nextblock
-        if send_nextblock_or_not(
-            param.nextblock_bundle_bribe,
-            &bl_nextblock
-        ) {
-            let bl = bl_nextblock.as_ref().unwrap();
-            execute_list.push(
-                self.send_tx(
-                    &param,
-                    instructions.clone(),
-                    Some(TYPE_NEXTBLOCK),
-                    bl.clone(),
-                    &sender
-                ),
+        if send_nextblock_or_not(param.nextblock_bundle_bribe, &bl_nextblock.first().cloned()) {
+            legs.push((TYPE_NEXTBLOCK, bl_nextblock));
+        }
+
+        if legs.is_empty() {
+            bail!("no relay to send to, none is both configured and bribed");
+        }
+
+        //sign once per relay, tip and priority fee differ between relays
+        let mut signed = vec![];
+        for (bundle_type, urls) in legs {
+            let transaction = self.sign_tx(
+                param,
+                instructions.clone(),
+                Some(bundle_type),
+                &self.fee_payer,
+                extra_signers,
+                true,
+            )?;
+            signed.push((bundle_type, urls, transaction));
+        }
+
+        let send_start = Instant::now();
+        let mut execute_list = vec![];
+        for (bundle_type, urls, transaction) in &signed {
+            for url in urls {
+                let sender = &sender;
+                execute_list.push(async move {
+                    let send_time = Instant::now();
+                    let result = self.send_signed_tx(transaction, Some(*bundle_type), url.clone(), sender).await;
+                    let elapsed = send_time.elapsed();
+                    let (response, error) = match result {
+                        Ok(mut response) => (response.pop(), None),
+                        Err(e) => (None, Some(e.to_string())),
+                    };
+                    record_relay_http(get_sender_name(*bundle_type), url, elapsed);
+                    EndpointOutcome {
+                        relay: get_sender_name(*bundle_type).to_string(),
+                        url: redact_url(url),
+                        signature: transaction.signatures[0].to_string(),
+                        response,
+                        error,
+                        elapsed_ms: elapsed.as_millis() as u32,
+                    }
+                });
+            }
+        }
+
+        let outcomes = join_all(execute_list).await;
+
+        record_stage(Stage::Send, send_start.elapsed());
+
+        let mut signatures: Vec<String> = vec![];
+        for (_, _, transaction) in &signed {
+            let signature = transaction.signatures[0].to_string();
+            if !signatures.contains(&signature) {
+                signatures.push(signature);
+            }
+        }
+
+        Ok(TradeReport {
+            signatures,
+            outcomes,
+            latency: LatencyBreakdown::default(), // filled in by `trade_detailed`
+        })
+    }
+
+
+    // pump.fun `create` followed by the creator's first buy in one transaction.
+    // with `bundle_buys` the launch goes out as a jito bundle carrying the extra buys,
+    // otherwise it takes the normal relay fan-out
+    pub async fn launch(
+        &self,
+        launch_param: &LaunchParam,
+    ) -> Result<(String, Vec<String>)> {
+
+        let param = &launch_param.swap;
+        if launch_param.bundle_buys.len() > MAX_BUNDLE_TRANSACTIONS - 1 {
+            bail!("at most {} bundle buys fit next to the launch", MAX_BUNDLE_TRANSACTIONS - 1);
+        }
+
+        let mint_keypair = match &launch_param.mint_secret_key {
+            Some(secret_key) => parse_secret_key(secret_key.expose())?,
+            None => Keypair::new(),
+        };
+        let mint = mint_keypair.pubkey();
+        let creator = self.fee_payer.pubkey();
+        let token_program_id = Pubkey::from_str(TOKEN_PROGRAM_ID)?;
+
+        let pumpfun_builder = Pumpfun::new(Pubkey::from_str(PUMPFUN_PROGRAM_ID)?);
+        let global = get_pumpfun_global(&self.rpc_client)?;
+        let mut curve = BundleCurve {
+            mint,
+            state: global.initial_bonding_curve(&creator),
+            fee_bps: global.total_fee_bps(),
+        };
+
+        //is_buy false, the creator ata can only be created after the mint exists
+        let creator_ata = get_associated_token_address(&creator, &mint);
+        let mut instructions = self.build_pub_instructions(
+            param,
+            false,
+            &mint,
+            &creator_ata,
+            true,
+        ).await?;
+
+        instructions.push(
+            pumpfun_builder.get_create_instruction(
+                &launch_param.name,
+                &launch_param.symbol,
+                &launch_param.uri,
+                &mint,
+                &creator,
+                &creator,
+            )?
+        );
+
+        if param.amount_in > 0 {
+            let token_amount = curve.state.simulate_buy(param.amount_in, curve.fee_bps);
+            let token_amount = (token_amount as u128
+                * 10_000u128.saturating_sub(param.slippage_amount as u128)
+                / 10_000) as u64;
+
+            instructions.push(
+                create_associated_token_account_idempotent(&creator, &creator, &mint, &token_program_id)
+            );
+            instructions.push(
+                pumpfun_builder.get_buy_instruction(
+                    &token_amount,
+                    &param.amount_in,
+                    &pumpfun_builder.get_account_inputs(
+                        &mint,
+                        &creator_ata,
+                        &creator,
+                        &global.next_fee_recipient(),
+                        &creator,
+                        None,
+                    )?,
+                )?
             );
         }
 
-        println!("trade send done {:?}", start.elapsed());
+        if launch_param.bundle_buys.is_empty() {
+            let responses = self.fan_out(param, instructions, &[&mint_keypair]).await?;
+            return Ok((mint.to_string(), responses));
+        }
 
-        let response = join_all(execute_list).await;
+        let bl_jito = env::var("STRATEGY_BIG_BLOCKENGINE_RUST")
+            .context("STRATEGY_BIG_BLOCKENGINE_RUST is required for bundle launches")?;
+
+        let mut transactions = vec![
+            self.sign_tx(param, instructions, Some(TYPE_JITO), &self.fee_payer, &[&mint_keypair], false)?
+        ];
+
+        let buyers: Vec<Keypair> = launch_param.bundle_buys.iter()
+            .map(|buy| buy.signer())
+            .collect::<Result<_>>()?;
+
+        for (index, (buy, buyer)) in launch_param.bundle_buys.iter().zip(buyers.iter()).enumerate() {
+            //the tip rides in the last transaction of the bundle
+            let is_last = index == launch_param.bundle_buys.len() - 1;
+            let (transaction, _token_amount, next_state) = self.build_buy_transaction(
+                param,
+                &curve,
+                buyer,
+                buy.amount_in,
+                &global.next_fee_recipient(),
+                is_last,
+            )?;
+            curve.state = next_state;
+            transactions.push(transaction);
+        }
 
-        println!("response {:?}", response);
+        let sender = ThirdPartySender::new()?;
+        let response = sender.send_jito_bundle(&transactions, &bl_jito, JITO_WALLET_KEY).await?;
 
-        let results: Vec<String> = response
-            .into_iter()
-            .flat_map(|result| {
-                result.unwrap_or_else(|e| vec![format!("HTTP请求错误: {}", e)])
-            })
-            .collect();
+        Ok((mint.to_string(), vec![bl_jito, response]))
+    }
 
-        Ok(results)
-
-        // for result in response {
-        //     match result {
-        //         Ok(signature) => {
-        //             // signatures = signature.clone();
-        //             println!("signature: {:?}", signature);
-        //         }
-        //         Err(e) => {
-        //             eprintln!("{:?}", e);
-        //         }
-        //     }
-        // }
-        //
-        // println!("trade rest {:?}", start.elapsed());
-        //
-        //
-        // Ok(signatures)
+    // one wallet's buy for a bundle, quoted on `curve` as left by the buys before it.
+    // returns the curve state after this buy, callers keep it once the buy is part of the bundle
+    pub fn build_buy_transaction(
+        &self,
+        param: &SwapParam,
+        curve: &BundleCurve,
+        buyer: &Keypair,
+        amount_in: u64,
+        fee_recipient: &Pubkey,
+        with_tip: bool,
+    ) -> Result<(VersionedTransaction, u64, BondingCurveInfo)> {
+        let mint = &curve.mint;
+        let mut bonding_curve = curve.state.clone();
+        let pumpfun_builder = Pumpfun::new(Pubkey::from_str(PUMPFUN_PROGRAM_ID)?);
+        let token_program_id = Pubkey::from_str(TOKEN_PROGRAM_ID)?;
+        let buyer_pubkey = buyer.pubkey();
+        let buyer_ata = get_associated_token_address(&buyer_pubkey, mint);
+
+        let token_amount = bonding_curve.simulate_buy(amount_in, curve.fee_bps);
+        let token_amount = (token_amount as u128
+            * 10_000u128.saturating_sub(param.slippage_amount as u128)
+            / 10_000) as u64;
+        if token_amount == 0 {
+            bail!("bonding curve quote is zero for {} lamports", amount_in);
+        }
+
+        let mut instructions = vec![];
+        if let Some(compute_unit) = param.compute_unit {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(compute_unit as u32));
+        }
+        instructions.push(
+            create_associated_token_account_idempotent(&buyer_pubkey, &buyer_pubkey, mint, &token_program_id)
+        );
+        instructions.push(
+            pumpfun_builder.get_buy_instruction(
+                &token_amount,
+                &amount_in,
+                &pumpfun_builder.get_account_inputs(
+                    mint,
+                    &buyer_ata,
+                    &buyer_pubkey,
+                    fee_recipient,
+                    &bonding_curve.creator,
+                    None,
+                )?,
+            )?
+        );
+
+        let transaction = self.sign_tx(param, instructions, Some(TYPE_JITO), buyer, &[], with_tip)?;
+        Ok((transaction, token_amount, bonding_curve))
+    }
+
+    // one buy per wallet on an existing mint, packed into jito bundles of up to
+    // MAX_BUNDLE_TRANSACTIONS with the tip in the last transaction of each bundle.
+    // every buy is quoted on the curve left by the buys before it, so bundle n's quotes assume
+    // bundles 0..n landed; bundles are sent in order and the rest are dropped once one fails
+    pub async fn bundle_buy(
+        &self,
+        bundle_param: &BundleBuyParam,
+    ) -> Result<Vec<BundleBuyOutcome>> {
+
+        let param = &bundle_param.swap;
+        let mint = Pubkey::from_str(&param.token_out)?;
+
+        let bonding_curve = self.get_bonding_curve_state(&mint)?
+            .ok_or_else(|| anyhow!("bonding curve not found for {}", mint))?;
+        if bonding_curve.complete {
+            bail!("bonding curve of {} is complete", mint);
+        }
+
+        let global = get_pumpfun_global(&self.rpc_client)?;
+        let mut curve = BundleCurve {
+            mint,
+            state: bonding_curve,
+            fee_bps: global.total_fee_bps(),
+        };
+
+        let bl_jito = env::var("STRATEGY_BIG_BLOCKENGINE_RUST")
+            .context("STRATEGY_BIG_BLOCKENGINE_RUST is required for bundle buys")?;
+
+        let wallets: Vec<Keypair> = bundle_param.wallets.iter()
+            .map(|wallet| wallet.signer())
+            .collect::<Result<_>>()?;
+
+        let mut outcomes = vec![];
+        let mut bundles: Vec<(u32, Vec<VersionedTransaction>)> = vec![];
+
+        for (bundle_index, chunk) in bundle_param.wallets
+            .iter()
+            .zip(wallets.iter())
+            .collect::<Vec<_>>()
+            .chunks(MAX_BUNDLE_TRANSACTIONS)
+            .enumerate()
+        {
+            let bundle_index = bundle_index as u32;
+            // (outcome index, transaction, curve before the buy) of every buy that built
+            let first_outcome = outcomes.len();
+            let mut built = vec![];
+            for (wallet, keypair) in chunk.iter() {
+                let result = self.build_buy_transaction(
+                    param,
+                    &curve,
+                    keypair,
+                    wallet.amount_in,
+                    &global.next_fee_recipient(),
+                    false,
+                );
+                outcomes.push(BundleBuyOutcome {
+                    wallet: keypair.pubkey().to_string(),
+                    bundle_index,
+                    token_amount: "0".to_string(),
+                    max_sol_cost: wallet.amount_in.to_string(),
+                    signature: None,
+                    response: None,
+                    error: None,
+                });
+                let outcome = outcomes.last_mut().unwrap();
+                match result {
+                    Ok((transaction, token_amount, next_state)) => {
+                        outcome.token_amount = token_amount.to_string();
+                        outcome.signature = Some(transaction.signatures[0].to_string());
+                        built.push((outcomes.len() - 1, transaction, std::mem::replace(&mut curve.state, next_state)));
+                    },
+                    Err(e) => outcome.error = Some(e.to_string()),
+                }
+            }
+
+            //the tip rides in the last transaction that built, re-signed from the same curve state.
+            //a buy that cannot be re-signed is dropped and the curve rewound to before it
+            while let Some((outcome_index, _, curve_before)) = built.pop() {
+                let (wallet, keypair) = chunk[outcome_index - first_outcome];
+                curve.state = curve_before;
+                let tipped = self.build_buy_transaction(
+                    param,
+                    &curve,
+                    keypair,
+                    wallet.amount_in,
+                    &global.next_fee_recipient(),
+                    true,
+                );
+                let outcome = &mut outcomes[outcome_index];
+                match tipped {
+                    Ok((transaction, _token_amount, next_state)) => {
+                        outcome.signature = Some(transaction.signatures[0].to_string());
+                        built.push((outcome_index, transaction, std::mem::replace(&mut curve.state, next_state)));
+                        break;
+                    },
+                    Err(e) => {
+                        outcome.signature = None;
+                        outcome.error = Some(e.to_string());
+                    },
+                }
+            }
+
+            if !built.is_empty() {
+                bundles.push((bundle_index, built.into_iter().map(|(_, transaction, _)| transaction).collect()));
+            }
+        }
+
+        let sender = ThirdPartySender::new()?;
+        let mut failed = false;
+        for (bundle_index, transactions) in bundles.iter() {
+            let response = if failed {
+                Err(anyhow!("not sent, an earlier bundle failed and this one was quoted on top of it"))
+            } else {
+                sender.send_jito_bundle(transactions, &bl_jito, JITO_WALLET_KEY).await
+                    .map_err(|e| anyhow!("HTTP请求错误: {}", e))
+            };
+            failed |= response.is_err();
+
+            for outcome in outcomes.iter_mut()
+                .filter(|o| o.bundle_index == *bundle_index && o.signature.is_some())
+            {
+                match &response {
+                    Ok(text) => outcome.response = Some(text.clone()),
+                    Err(e) => outcome.error = Some(e.to_string()),
+                }
+            }
+        }
+
+        Ok(outcomes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
 
+    // a trade whose only relay is down, the key travels in the env var url as temporal takes it
+    #[tokio::test]
+    async fn failed_relay_send_never_logs_the_key() {
+        std::env::set_var("TEMPORAL_BLOCKENGINE_RUST", "http://127.0.0.1:1/?c=abc123");
+        let payer = Keypair::new();
+        let param: SwapParam = serde_json::from_value(json!({
+            "connection": "http://127.0.0.1:1",
+            "connection_brand": "",
+            "secret_key": payer.to_base58_string(),
+            "amount_in": 0,
+            "amount_out": 0,
+            "fixed_side": "in",
+            "target_pool": "",
+            "token_in": "",
+            "token_out": "",
+            "decimals_in": 9,
+            "decimals_out": 6,
+            "slippage_amount": 0,
+            "temporal_bundle_bribe": 1_000_000,
+            "temporal_recent_block_hash": Hash::default().to_string(),
+        })).unwrap();
+
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_max_level(tracing::Level::DEBUG)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let report = TransactionBuilder::new(param.connection.clone(), payer)
+            .fan_out_detailed(&param, vec![], &[])
+            .await
+            .unwrap();
+
+        let outcome = &report.outcomes[0];
+        assert_eq!(outcome.url, "http://127.0.0.1:1/?c=***");
+        let error = outcome.error.as_deref().unwrap();
+        assert!(!error.contains("abc123"), "{}", error);
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("send failed"), "{}", output);
+        assert!(!output.contains("abc123"), "{}", output);
     }
 }
@@ -1,4 +1,5 @@
 use std::sync::OnceLock;
+use anyhow::{anyhow, bail, Result};
 use std::time::Duration;
 use serde::Deserialize;
 use solana_program::instruction::Instruction;
@@ -13,6 +14,11 @@ use crate::configs::bribe::{
     get_slot0_trade_random_tip_account,
     get_temporal_random_tip_account
 };
+use crate::latency::LatencyBreakdown;
+use crate::secret::{redact_url, Secret};
+use crate::keystore::resolve_signer;
+use solana_sdk::signature::Keypair;
+use std::fmt;
 use napi_derive::napi;
 use reqwest::Client;
 
@@ -38,7 +44,7 @@ pub struct SwapParam {
     // pub connection_send: String,
     // pub connection_send_brand: String,
     // pub trade_type : String,
-    pub secret_key : String,
+    pub secret_key : Secret<String>,
     pub amount_in : u64,
     pub amount_out : u64,
     pub fixed_side : String,
@@ -70,9 +76,9 @@ pub struct SwapParam {
 
     pub buy_once: Option<bool>,
     pub max_block_number: Option<u64>,
-    pub token_balance: Option<u64>,
-    pub token_mint: Option<String>,
-    pub market_id: Option<String>,
+    pub token_balance: Option<u64>, // unused, sells are sized with sell_mode
+    pub token_mint: Option<String>, // unused, sells are sized with sell_mode
+    pub market_id: Option<String>, // OpenBook market of the raydium pool, the amm is derived from it
 
     pub skip_retry: Option<bool>,
     pub group_id: Option<u64>,
@@ -118,16 +124,31 @@ pub struct SwapParam {
     pub anti_mev: Option<u8>, // third-party api prevents MEV, 0 no, 1 yes
 
     pub creator_vault: Option<String>,
+
+    pub pumpfun_direct: Option<bool>, // call the pump.fun program directly instead of the proxy
+
+    pub sell_mode: Option<String>, // "100%", "50%", "all", "all-but:<amount>", overrides amount_in on sells
+
+    pub region_fan_out: Option<u8>, // fastest regions per relay to send to when block_engine_locate is unset
+
+    pub wallet_id: Option<String>, // label of a wallet loaded into the keystore, replaces secret_key
+}
+
+impl SwapParam {
+
+    pub fn signer(&self) -> Result<Keypair> {
+        resolve_signer(self.wallet_id.as_deref(), &self.secret_key)
+    }
 }
 
 
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Deserialize, Clone)]
 #[napi(object)]
 pub struct SwapParam4Node {
     pub connection: String,
     pub connection_brand : String,
-    pub secret_key : String,
+    pub secret_key : Option<String>, // base58, not needed with wallet_id
     pub amount_in : String,
     pub amount_out : String,
     pub fixed_side : String,
@@ -161,7 +182,7 @@ pub struct SwapParam4Node {
     pub max_block_number: Option<String>,
     pub token_balance: Option<String>,
     pub token_mint: Option<String>,
-    pub market_id: Option<String>,
+    pub market_id: Option<String>, // OpenBook market of the raydium pool, the amm is derived from it
 
     pub skip_retry: Option<bool>,
     pub group_id: Option<String>,
@@ -207,6 +228,270 @@ pub struct SwapParam4Node {
     pub anti_mev: Option<String>, // third-party api prevents MEV, 0 no, 1 yes
 
     pub creator_vault: Option<String>,
+
+    pub pumpfun_direct: Option<bool>, // call the pump.fun program directly instead of the proxy
+
+    pub sell_mode: Option<String>, // "100%", "50%", "all", "all-but:<amount>", overrides amount_in on sells
+
+    pub region_fan_out: Option<String>, // fastest regions per relay to send to when block_engine_locate is unset
+
+    pub wallet_id: Option<String>, // label of a wallet loaded with `loadWallet` / `importWallet`
+}
+
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SeedAccountParam {
+    pub connection: String,
+    pub secret_key: Secret<String>,
+    pub seed: String,
+    pub mint: String,
+    pub lamports: u64, // wsol funding, only for native mint sub-accounts
+    pub wallet_id: Option<String>, // label of a wallet loaded into the keystore, replaces secret_key
+}
+
+impl SeedAccountParam {
+
+    pub fn signer(&self) -> Result<Keypair> {
+        resolve_signer(self.wallet_id.as_deref(), &self.secret_key)
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[napi(object)]
+pub struct SeedAccountParam4Node {
+    pub connection: String,
+    pub secret_key: Option<String>, // base58, not needed with wallet_id
+    pub seed: String,
+    pub mint: String,
+    pub lamports: Option<String>,
+    pub wallet_id: Option<String>, // label of a wallet loaded with `loadWallet` / `importWallet`
+}
+
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct SeedAccountResult {
+    pub public_key: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BundleBuy {
+    pub secret_key: Secret<String>,
+    pub amount_in: u64,
+    pub wallet_id: Option<String>, // label of a wallet loaded into the keystore, replaces secret_key
+}
+
+impl BundleBuy {
+
+    pub fn signer(&self) -> Result<Keypair> {
+        resolve_signer(self.wallet_id.as_deref(), &self.secret_key)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct LaunchParam {
+    pub swap: SwapParam, // fees, bribes and compute settings; amount_in is the dev buy
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub mint_secret_key: Option<Secret<String>>, // a fresh mint keypair is generated when empty
+    pub bundle_buys: Vec<BundleBuy>, // extra buys sent with the launch in one jito bundle
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BundleBuyParam {
+    pub swap: SwapParam, // token_out is the mint, fees, bribes and slippage apply to every wallet
+    pub wallets: Vec<BundleBuy>,
+}
+
+#[derive(Deserialize, Clone)]
+#[napi(object)]
+pub struct BundleBuy4Node {
+    pub secret_key: Option<String>, // base58, not needed with wallet_id
+    pub amount_in: String,
+    pub wallet_id: Option<String>, // label of a wallet loaded with `loadWallet` / `importWallet`
+}
+
+#[derive(Deserialize, Clone)]
+#[napi(object)]
+pub struct LaunchParam4Node {
+    pub swap: SwapParam4Node,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub mint_secret_key: Option<String>,
+    pub bundle_buys: Option<Vec<BundleBuy4Node>>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[napi(object)]
+pub struct BundleBuyParam4Node {
+    pub swap: SwapParam4Node,
+    pub wallets: Vec<BundleBuy4Node>,
+}
+
+// node params carry raw secrets, Debug masks them
+
+impl fmt::Debug for SwapParam4Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SwapParam4Node")
+            .field("connection", &redact_url(&self.connection))
+            .field("secret_key", &self.secret_key.as_ref().map(|_| Secret::new(())))
+            .field("wallet_id", &self.wallet_id)
+            .field("token_in", &self.token_in)
+            .field("token_out", &self.token_out)
+            .field("amount_in", &self.amount_in)
+            .field("amount_out", &self.amount_out)
+            .field("slippage_amount", &self.slippage_amount)
+            .field("block_engine_locate", &self.block_engine_locate)
+            .field("sell_mode", &self.sell_mode)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for SeedAccountParam4Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SeedAccountParam4Node")
+            .field("connection", &redact_url(&self.connection))
+            .field("secret_key", &self.secret_key.as_ref().map(|_| Secret::new(())))
+            .field("wallet_id", &self.wallet_id)
+            .field("seed", &self.seed)
+            .field("mint", &self.mint)
+            .field("lamports", &self.lamports)
+            .finish()
+    }
+}
+
+impl fmt::Debug for BundleBuy4Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BundleBuy4Node")
+            .field("secret_key", &self.secret_key.as_ref().map(|_| Secret::new(())))
+            .field("wallet_id", &self.wallet_id)
+            .field("amount_in", &self.amount_in)
+            .finish()
+    }
+}
+
+impl fmt::Debug for LaunchParam4Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LaunchParam4Node")
+            .field("swap", &self.swap)
+            .field("name", &self.name)
+            .field("symbol", &self.symbol)
+            .field("uri", &self.uri)
+            .field("mint_secret_key", &self.mint_secret_key.as_ref().map(|_| Secret::new(())))
+            .field("bundle_buys", &self.bundle_buys)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct BundleBuyOutcome {
+    pub wallet: String,
+    pub bundle_index: u32,
+    pub token_amount: String,
+    pub max_sol_cost: String,
+    pub signature: Option<String>,
+    pub response: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct EndpointOutcome {
+    pub relay: String,
+    pub url: String,
+    pub signature: String,
+    pub response: Option<String>,
+    pub error: Option<String>,
+    pub elapsed_ms: u32,
+}
+
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct TradeReport {
+    pub signatures: Vec<String>, // one per relay, regions of a relay share it
+    pub outcomes: Vec<EndpointOutcome>,
+    pub latency: LatencyBreakdown,
+}
+
+impl TradeReport {
+
+    // flat [url, response, ...] list returned by `swap`
+    pub fn to_responses(&self) -> Vec<String> {
+        self.outcomes.iter()
+            .flat_map(|outcome| match (&outcome.response, &outcome.error) {
+                (Some(response), None) => vec![outcome.url.clone(), response.clone()],
+                (_, error) => vec![format!("HTTP请求错误: {}", error.clone().unwrap_or_default())],
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct CloseTokenAccountsResult {
+    pub closed: Vec<String>,
+    pub signatures: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct LaunchResult {
+    pub mint: String,
+    pub responses: Vec<String>,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SellMode {
+    All,
+    Percent(u64), // basis points of the balance
+    AllBut(u64),  // keep this many raw token units
+}
+
+impl SellMode {
+
+    pub fn parse(mode: &str) -> Result<SellMode> {
+        let mode = mode.trim().to_lowercase();
+        if mode == "all" || mode == "100%" {
+            return Ok(SellMode::All);
+        }
+        if let Some(keep) = mode.strip_prefix("all-but:").or_else(|| mode.strip_prefix("all but ")) {
+            return Ok(SellMode::AllBut(keep.trim().parse()?));
+        }
+        if let Some(percent) = mode.strip_suffix('%') {
+            let percent: f64 = percent.trim().parse()?;
+            if !(percent > 0.0 && percent <= 100.0) {
+                bail!("sell percentage must be in (0, 100], got {}", percent);
+            }
+            return Ok(SellMode::Percent((percent * 100.0).round() as u64));
+        }
+        Err(anyhow!("unknown sell_mode {:?}", mode))
+    }
+
+    pub fn resolve(&self, balance: u64) -> u64 {
+        match self {
+            SellMode::All => balance,
+            SellMode::Percent(bps) => (balance as u128 * *bps as u128 / 10_000) as u64,
+            SellMode::AllBut(keep) => balance.saturating_sub(*keep),
+        }
+    }
+
+    // the account ends up empty, so it can be closed in the same transaction
+    pub fn closes_account(&self) -> bool {
+        *self == SellMode::All
+    }
+}
+
+// min out quoted for `quoted_in` tokens, scaled to the `amount_in` actually sold.
+// left as is when nothing was quoted
+pub fn scale_amount_out(amount_out: u64, quoted_in: u64, amount_in: u64) -> u64 {
+    if quoted_in == 0 {
+        return amount_out;
+    }
+    (amount_out as u128 * amount_in as u128 / quoted_in as u128) as u64
 }
 
 
@@ -279,3 +564,44 @@ pub fn get_client() -> &'static Client {
         .expect("Failed to create HTTP client")
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sell_modes() {
+        assert_eq!(SellMode::parse("all").unwrap(), SellMode::All);
+        assert_eq!(SellMode::parse(" ALL ").unwrap(), SellMode::All);
+        assert_eq!(SellMode::parse("100%").unwrap(), SellMode::All);
+        assert_eq!(SellMode::parse("50%").unwrap(), SellMode::Percent(5_000));
+        assert_eq!(SellMode::parse("12.5 %").unwrap(), SellMode::Percent(1_250));
+        assert_eq!(SellMode::parse("all-but:1000").unwrap(), SellMode::AllBut(1_000));
+        assert_eq!(SellMode::parse("all but 42").unwrap(), SellMode::AllBut(42));
+    }
+
+    #[test]
+    fn rejects_bad_sell_modes() {
+        for mode in ["", "half", "0%", "-5%", "101%", "all-but:", "all-but:x"] {
+            assert!(SellMode::parse(mode).is_err(), "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn resolves_against_the_balance() {
+        assert_eq!(SellMode::All.resolve(1_000), 1_000);
+        assert_eq!(SellMode::Percent(5_000).resolve(1_001), 500);
+        assert_eq!(SellMode::AllBut(300).resolve(1_000), 700);
+        assert_eq!(SellMode::AllBut(3_000).resolve(1_000), 0);
+        assert!(SellMode::All.closes_account());
+        assert!(!SellMode::Percent(10_000).closes_account());
+    }
+
+    #[test]
+    fn scales_min_out_to_the_resolved_amount() {
+        assert_eq!(scale_amount_out(1_000_000, 1_000, 500), 500_000);
+        assert_eq!(scale_amount_out(1_000_000, 1_000, 3_000), 3_000_000);
+        assert_eq!(scale_amount_out(u64::MAX, u64::MAX, u64::MAX / 2), u64::MAX / 2);
+        assert_eq!(scale_amount_out(1_000_000, 0, 500), 1_000_000);
+    }
+}